@@ -0,0 +1,188 @@
+use crate::parser::JsonObject;
+
+#[derive(Debug, PartialEq)]
+pub enum SerializeError {
+    /// BSON documents must be objects or arrays at the top level.
+    InvalidRoot,
+    /// Keys are written as C strings and so cannot contain NUL bytes.
+    KeyContainsNul(String),
+    /// BSON integers are at most 64 bits wide.
+    IntegerTooWide(i128),
+    /// A string or document is longer than the `i32::MAX` bytes its length
+    /// prefix can describe.
+    TooLong(usize),
+}
+
+/// Serializes an object or array as a BSON document. Arrays become documents
+/// keyed by index, as BSON arrays are.
+///
+/// `Integer` values are written as int32 when they fit and int64 otherwise.
+/// `Number` values are always written as doubles, so they decode back as
+/// floats and `-0.0` keeps its sign.
+pub fn serialize(object: &JsonObject) -> Result<Vec<u8>, SerializeError> {
+    match object {
+        JsonObject::Object(elements) => serialize_document(elements),
+        JsonObject::Array(elements) => serialize_array(elements),
+        _ => Err(SerializeError::InvalidRoot),
+    }
+}
+
+fn serialize_array(elements: &[JsonObject]) -> Result<Vec<u8>, SerializeError> {
+    serialize_document(
+        elements
+            .iter()
+            .enumerate()
+            .map(|(i, element)| (i.to_string(), element)),
+    )
+}
+
+fn serialize_document<'a, K: AsRef<str>>(
+    entries: impl IntoIterator<Item = (K, &'a JsonObject)>,
+) -> Result<Vec<u8>, SerializeError> {
+    let mut document = vec![0; 4];
+
+    for (key, value) in entries {
+        serialize_element(&mut document, key.as_ref(), value)?;
+    }
+    document.push(0x00);
+
+    let len = encode_len(document.len())?;
+    document[..4].copy_from_slice(&len);
+    Ok(document)
+}
+
+fn serialize_element(
+    document: &mut Vec<u8>,
+    key: &str,
+    value: &JsonObject,
+) -> Result<(), SerializeError> {
+    if key.contains('\0') {
+        return Err(SerializeError::KeyContainsNul(key.to_string()));
+    }
+
+    let (element_type, payload) = match value {
+        JsonObject::Object(elements) => (0x03, serialize_document(elements)?),
+        JsonObject::Array(elements) => (0x04, serialize_array(elements)?),
+        JsonObject::String(val) => {
+            let mut payload = encode_len(val.len() + 1)?.to_vec();
+            payload.extend(val.as_bytes());
+            payload.push(0x00);
+            (0x02, payload)
+        }
+        JsonObject::Integer(val) => match i32::try_from(*val) {
+            Ok(val) => (0x10, val.to_le_bytes().to_vec()),
+            Err(_) => (0x12, val.to_le_bytes().to_vec()),
+        },
+        JsonObject::Number(val) => (0x01, val.to_le_bytes().to_vec()),
+        JsonObject::WideInteger(val) => return Err(SerializeError::IntegerTooWide(*val)),
+        JsonObject::Boolean(val) => (0x08, vec![u8::from(*val)]),
+        JsonObject::Null => (0x0a, vec![]),
+    };
+
+    document.push(element_type);
+    document.extend(key.as_bytes());
+    document.push(0x00);
+    document.extend(payload);
+    Ok(())
+}
+
+/// Encodes a length prefix, which BSON stores as a signed 32-bit integer.
+fn encode_len(len: usize) -> Result<[u8; 4], SerializeError> {
+    match i32::try_from(len) {
+        Ok(len) => Ok(len.to_le_bytes()),
+        Err(_) => Err(SerializeError::TooLong(len)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn object(entries: Vec<(&str, JsonObject)>) -> JsonObject {
+        JsonObject::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_serialize_string_document() {
+        let json = object(vec![("hello", JsonObject::String("world".to_string()))]);
+
+        assert_eq!(
+            serialize(&json).unwrap(),
+            b"\x16\x00\x00\x00\x02hello\x00\x06\x00\x00\x00world\x00\x00".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_serialize_array_document() {
+        let json = object(vec![(
+            "a",
+            JsonObject::Array(vec![JsonObject::Integer(1), JsonObject::Null]),
+        )]);
+
+        assert_eq!(
+            serialize(&json).unwrap(),
+            b"\x17\x00\x00\x00\x04a\x00\x0f\x00\x00\x00\x100\x00\x01\x00\x00\x00\x0a1\x00\x00\x00"
+                .to_vec()
+        );
+    }
+
+    #[test]
+    fn test_serialize_scalars() {
+        let document = |value| serialize(&object(vec![("x", value)])).unwrap()[4..].to_vec();
+
+        assert_eq!(document(JsonObject::Boolean(true)), b"\x08x\x00\x01\x00");
+        assert_eq!(
+            document(JsonObject::Number(0.5)),
+            b"\x01x\x00\x00\x00\x00\x00\x00\x00\xe0\x3f\x00"
+        );
+        assert_eq!(
+            document(JsonObject::Integer(-5_000_000_000)),
+            b"\x12x\x00\x00\x0e\xfa\xd5\xfe\xff\xff\xff\x00"
+        );
+    }
+
+    #[test]
+    fn test_serialize_numbers_as_doubles() {
+        let document = |value| serialize(&object(vec![("x", value)])).unwrap()[4..].to_vec();
+
+        assert_eq!(
+            document(JsonObject::Number(3.0)),
+            b"\x01x\x00\x00\x00\x00\x00\x00\x00\x08\x40\x00"
+        );
+        assert_eq!(
+            document(JsonObject::Number(-0.0)),
+            b"\x01x\x00\x00\x00\x00\x00\x00\x00\x00\x80\x00"
+        );
+        assert_eq!(
+            document(JsonObject::Integer(3)),
+            b"\x10x\x00\x03\x00\x00\x00\x00"
+        );
+    }
+
+    #[test]
+    fn test_serialize_errors() {
+        assert_eq!(
+            serialize(&JsonObject::Null),
+            Err(SerializeError::InvalidRoot)
+        );
+        assert_eq!(
+            serialize(&JsonObject::Object(HashMap::from([(
+                "a\0b".to_string(),
+                JsonObject::Null
+            )]))),
+            Err(SerializeError::KeyContainsNul("a\0b".to_string()))
+        );
+        assert_eq!(encode_len(i32::MAX as usize), Ok(i32::MAX.to_le_bytes()));
+        assert_eq!(
+            encode_len(i32::MAX as usize + 1),
+            Err(SerializeError::TooLong(i32::MAX as usize + 1))
+        );
+    }
+}
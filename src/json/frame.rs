@@ -0,0 +1,99 @@
+use std::io::{self, Read, Write};
+
+use super::serialize::to_string;
+use crate::parser::{from_str, token::ParseError, JsonObject};
+
+#[derive(Debug)]
+pub enum FrameError {
+    Io(io::Error),
+    InvalidUtf8,
+    Parse(ParseError),
+}
+
+impl From<io::Error> for FrameError {
+    fn from(error: io::Error) -> Self {
+        FrameError::Io(error)
+    }
+}
+
+impl From<ParseError> for FrameError {
+    fn from(error: ParseError) -> Self {
+        FrameError::Parse(error)
+    }
+}
+
+/// Writes `object` as JSON text preceded by its length in bytes, as a 4-byte
+/// big-endian integer.
+pub fn write_framed<W: Write>(object: &JsonObject, writer: &mut W) -> io::Result<()> {
+    let json = to_string(object);
+    let len = u32::try_from(json.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "frame exceeds u32::MAX bytes"))?;
+
+    writer.write_all(&len.to_be_bytes())?;
+    writer.write_all(json.as_bytes())
+}
+
+/// Reads one frame written by `write_framed` and parses its contents.
+pub fn read_framed<R: Read>(reader: &mut R) -> Result<JsonObject, FrameError> {
+    let mut len = [0; 4];
+    reader.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len);
+
+    // The length comes from the peer, so read into a growing buffer rather
+    // than trusting it for an up-front allocation.
+    let mut bytes = vec![];
+    reader.take(len.into()).read_to_end(&mut bytes)?;
+    if bytes.len() < len as usize {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+
+    let json = std::str::from_utf8(&bytes).map_err(|_| FrameError::InvalidUtf8)?;
+    Ok(from_str(json)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn round_trips_frames() {
+        let first = from_str(r#"{"id": 1, "params": [true, "x"]}"#).unwrap();
+        let second = JsonObject::Null;
+
+        let mut buffer = vec![];
+        write_framed(&first, &mut buffer).unwrap();
+        write_framed(&second, &mut buffer).unwrap();
+        assert_eq!(buffer[..4], [0, 0, 0, to_string(&first).len() as u8]);
+
+        let mut reader = Cursor::new(buffer);
+        assert_eq!(read_framed(&mut reader).unwrap(), first);
+        assert_eq!(read_framed(&mut reader).unwrap(), second);
+        assert!(matches!(
+            read_framed(&mut reader),
+            Err(FrameError::Io(error)) if error.kind() == io::ErrorKind::UnexpectedEof
+        ));
+    }
+
+    #[test]
+    fn read_framed_errors() {
+        let mut reader = Cursor::new(b"\x00\x00\x00\x05[1]".to_vec());
+        assert!(matches!(
+            read_framed(&mut reader),
+            Err(FrameError::Io(error)) if error.kind() == io::ErrorKind::UnexpectedEof
+        ));
+
+        let mut reader = Cursor::new(b"\x00\x00\x00\x03\"\xff\"".to_vec());
+        assert!(matches!(
+            read_framed(&mut reader),
+            Err(FrameError::InvalidUtf8)
+        ));
+
+        let mut reader = Cursor::new(b"\x00\x00\x00\x01a".to_vec());
+        assert!(matches!(
+            read_framed(&mut reader),
+            Err(FrameError::Parse(ParseError::InvalidToken))
+        ));
+    }
+}
@@ -0,0 +1,399 @@
+use std::{
+    cmp::Ordering,
+    fmt::{self, Write},
+};
+
+use crate::parser::JsonObject;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub enum KeyOrder {
+    /// Keys are written in the object's iteration order, which is unspecified
+    /// for `HashMap`-backed objects.
+    #[default]
+    AsStored,
+    /// Keys are sorted lexicographically at every depth. Objects are stored in
+    /// a `HashMap`, which has no order to sort in place, so this is the way to
+    /// get canonical output.
+    Sorted,
+    Custom(fn(&str, &str) -> Ordering),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FormatOptions {
+    pub key_order: KeyOrder,
+    /// Spaces per nesting level. With `None`, the output is written on one
+    /// line without any whitespace.
+    pub indent: Option<usize>,
+    /// Escape U+2028 and U+2029. Both are valid in JSON strings but end the
+    /// line in JavaScript source, so this is needed when embedding the output
+    /// in a `<script>` tag.
+    pub escape_js_separators: bool,
+    /// Write integral `Number` values with a decimal point, such as `1.0`
+    /// instead of `1`, so they parse back as a `Number` rather than an
+    /// `Integer`.
+    pub keep_decimal_point: bool,
+}
+
+/// Serializes `object` as compact JSON text.
+///
+/// Non-finite numbers have no JSON representation and are written as `null`.
+pub fn to_string(object: &JsonObject) -> String {
+    to_string_with_options(object, &FormatOptions::default())
+}
+
+/// Serializes `object` as JSON text with each array element and object entry
+/// on its own line, indented by `indent` spaces per level. Empty arrays and
+/// objects stay on one line.
+pub fn to_string_pretty(object: &JsonObject, indent: usize) -> String {
+    let options = FormatOptions {
+        indent: Some(indent),
+        ..Default::default()
+    };
+    to_string_with_options(object, &options)
+}
+
+pub fn to_string_with_options(object: &JsonObject, options: &FormatOptions) -> String {
+    let mut output = String::new();
+    write_value(&mut output, object, options, 0);
+    output
+}
+
+/// Formats the value as compact JSON, like `to_string`.
+impl fmt::Display for JsonObject {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&to_string(self))
+    }
+}
+
+impl From<&JsonObject> for String {
+    fn from(object: &JsonObject) -> Self {
+        to_string(object)
+    }
+}
+
+fn write_value(output: &mut String, object: &JsonObject, options: &FormatOptions, depth: usize) {
+    match object {
+        JsonObject::Object(elements) => {
+            let mut entries: Vec<_> = elements.iter().collect();
+            match options.key_order {
+                KeyOrder::AsStored => {}
+                KeyOrder::Sorted => entries.sort_by_key(|(key, _)| *key),
+                KeyOrder::Custom(compare) => entries.sort_by(|(a, _), (b, _)| compare(a, b)),
+            }
+
+            output.push('{');
+            for (i, (key, value)) in entries.iter().enumerate() {
+                if i > 0 {
+                    output.push(',');
+                }
+                write_line_break(output, options, depth + 1);
+                write_string(output, key, options);
+                output.push(':');
+                if options.indent.is_some() {
+                    output.push(' ');
+                }
+                write_value(output, value, options, depth + 1);
+            }
+            if !entries.is_empty() {
+                write_line_break(output, options, depth);
+            }
+            output.push('}');
+        }
+        JsonObject::Array(elements) => {
+            output.push('[');
+            for (i, element) in elements.iter().enumerate() {
+                if i > 0 {
+                    output.push(',');
+                }
+                write_line_break(output, options, depth + 1);
+                write_value(output, element, options, depth + 1);
+            }
+            if !elements.is_empty() {
+                write_line_break(output, options, depth);
+            }
+            output.push(']');
+        }
+        JsonObject::String(value) => write_string(output, value, options),
+        // `{:?}` keeps the decimal point of integral values.
+        JsonObject::Number(value) if value.is_finite() && options.keep_decimal_point => {
+            write!(output, "{:?}", value).unwrap()
+        }
+        JsonObject::Number(value) if value.is_finite() => write!(output, "{}", value).unwrap(),
+        JsonObject::Number(_) | JsonObject::Null => output.push_str("null"),
+        JsonObject::Integer(value) => write!(output, "{}", value).unwrap(),
+        JsonObject::WideInteger(value) => write!(output, "{}", value).unwrap(),
+        JsonObject::Boolean(value) => write!(output, "{}", value).unwrap(),
+    }
+}
+
+/// Starts a new line indented for `depth` when pretty-printing.
+fn write_line_break(output: &mut String, options: &FormatOptions, depth: usize) {
+    if let Some(indent) = options.indent {
+        output.push('\n');
+        output.push_str(&" ".repeat(indent * depth));
+    }
+}
+
+fn write_string(output: &mut String, value: &str, options: &FormatOptions) {
+    output.push('"');
+    escape_into(output, value, false, options.escape_js_separators);
+    output.push('"');
+}
+
+/// Escapes `value` for use inside a JSON string literal, without the
+/// surrounding quotes.
+pub fn escape_json_string(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    escape_into(&mut output, value, false, false);
+    output
+}
+
+/// Like `escape_json_string`, but also escapes every non-ASCII character as
+/// `\uXXXX` (using surrogate pairs outside the BMP).
+pub fn escape_json_string_ascii(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    escape_into(&mut output, value, true, false);
+    output
+}
+
+fn escape_into(output: &mut String, value: &str, ascii_only: bool, js_separators: bool) {
+    for char in value.chars() {
+        match char {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            '\u{8}' => output.push_str("\\b"),
+            '\u{c}' => output.push_str("\\f"),
+            '\u{0}'..='\u{1f}' => write!(output, "\\u{:04x}", char as u32).unwrap(),
+            '\u{2028}' | '\u{2029}' if js_separators => {
+                write!(output, "\\u{:04x}", char as u32).unwrap()
+            }
+            _ if ascii_only && !char.is_ascii() => {
+                for unit in char.encode_utf16(&mut [0; 2]) {
+                    write!(output, "\\u{:04x}", unit).unwrap();
+                }
+            }
+            _ => output.push(char),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn test_to_string_compact() {
+        let mut map = HashMap::new();
+        map.insert(
+            "a\"b".to_string(),
+            JsonObject::Array(vec![
+                JsonObject::Number(1.0),
+                JsonObject::Number(-2.5),
+                JsonObject::String("x\ny".to_string()),
+                JsonObject::Boolean(false),
+                JsonObject::Null,
+            ]),
+        );
+        let json = JsonObject::Object(map);
+
+        assert_eq!(to_string(&json), r#"{"a\"b":[1,-2.5,"x\ny",false,null]}"#);
+    }
+
+    #[test]
+    fn test_to_string_key_order() {
+        let inner = HashMap::from([
+            ("b".to_string(), JsonObject::Null),
+            ("a".to_string(), JsonObject::Null),
+        ]);
+        let json = JsonObject::Object(HashMap::from([
+            ("zz".to_string(), JsonObject::Object(inner)),
+            ("y".to_string(), JsonObject::Null),
+            ("xxx".to_string(), JsonObject::Null),
+        ]));
+
+        let sorted = FormatOptions {
+            key_order: KeyOrder::Sorted,
+            ..Default::default()
+        };
+        assert_eq!(
+            to_string_with_options(&json, &sorted),
+            r#"{"xxx":null,"y":null,"zz":{"a":null,"b":null}}"#
+        );
+
+        let by_length_descending = FormatOptions {
+            key_order: KeyOrder::Custom(|a, b| b.len().cmp(&a.len()).then(b.cmp(a))),
+            ..Default::default()
+        };
+        assert_eq!(
+            to_string_with_options(&json, &by_length_descending),
+            r#"{"xxx":null,"zz":{"b":null,"a":null},"y":null}"#
+        );
+    }
+
+    #[test]
+    fn test_to_string_sorted_at_all_depths() {
+        let json = crate::parser::from_str(
+            r#"{"c": [{"z": 1, "y": {"q": null, "p": null}}], "a": {"n": 1, "m": 2}, "b": 3}"#,
+        )
+        .unwrap();
+        let sorted = FormatOptions {
+            key_order: KeyOrder::Sorted,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            to_string_with_options(&json, &sorted),
+            r#"{"a":{"m":2,"n":1},"b":3,"c":[{"y":{"p":null,"q":null},"z":1}]}"#
+        );
+    }
+
+    #[test]
+    fn test_to_string_round_trips_through_parser() {
+        use crate::parser::{parse_tokens, token::tokenize};
+
+        let parse = |input: &str| parse_tokens(tokenize(input.to_string()).unwrap()).unwrap();
+
+        for input in [
+            r#"{"a": [1, 2.5, -0.125, 1e21], "b": {"c": null, "d": [true, false]}}"#,
+            r#"["quote \" backslash \\ slash \/", "tab\t nl\n cr\r", "\u0001\u001f\u007f"]"#,
+            r#"{"caf\u00e9 \ud83d\ude00": "", "": {}}"#,
+            "[[], [[]], {}]",
+        ] {
+            let json = parse(input);
+            let output = to_string(&json);
+            assert_eq!(parse(&output), json, "{}", output);
+        }
+
+        assert_eq!(to_string(&parse("[1.0, 2.50, 1e2]")), "[1,2.5,100]");
+    }
+
+    #[test]
+    fn test_to_string_keeps_decimal_point() {
+        let options = FormatOptions {
+            keep_decimal_point: true,
+            ..Default::default()
+        };
+        let input = "[1.0,1,-0.0,2.5,1e21,-7]";
+        let json = crate::parser::from_str(input).unwrap();
+        let output = to_string_with_options(&json, &options);
+        assert_eq!(output, "[1.0,1,-0.0,2.5,1e21,-7]");
+        assert_eq!(to_string(&json), "[1,1,-0,2.5,1000000000000000000000,-7]");
+
+        let JsonObject::Array(elements) = crate::parser::from_str(&output).unwrap() else {
+            panic!("Expected array");
+        };
+        assert!(matches!(elements[0], JsonObject::Number(value) if value == 1.0));
+        assert!(matches!(elements[1], JsonObject::Integer(1)));
+        assert!(matches!(elements[2], JsonObject::Number(value) if value.is_sign_negative()));
+        assert!(matches!(elements[4], JsonObject::Number(_)));
+        assert!(matches!(elements[5], JsonObject::Integer(-7)));
+    }
+
+    #[test]
+    fn test_to_string_pretty() {
+        let json = crate::parser::from_str(
+            r#"{"name": "x", "list": [1, [], {"deep": [true]}], "empty": {}}"#,
+        )
+        .unwrap();
+        let options = FormatOptions {
+            key_order: KeyOrder::Sorted,
+            indent: Some(2),
+            ..Default::default()
+        };
+
+        let expected = r#"{
+  "empty": {},
+  "list": [
+    1,
+    [],
+    {
+      "deep": [
+        true
+      ]
+    }
+  ],
+  "name": "x"
+}"#;
+        assert_eq!(to_string_with_options(&json, &options), expected);
+
+        assert_eq!(to_string_pretty(&JsonObject::Array(vec![]), 2), "[]");
+        assert_eq!(
+            to_string_pretty(&crate::parser::from_str(r#"{"a": [null]}"#).unwrap(), 4),
+            "{\n    \"a\": [\n        null\n    ]\n}"
+        );
+    }
+
+    #[test]
+    fn test_to_string_escapes_js_separators() {
+        let json = JsonObject::Object(HashMap::from([(
+            "line\u{2028}".to_string(),
+            JsonObject::String("a\u{2028}b\u{2029}c".to_string()),
+        )]));
+        let options = FormatOptions {
+            escape_js_separators: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            to_string_with_options(&json, &options),
+            r#"{"line\u2028":"a\u2028b\u2029c"}"#
+        );
+        assert_eq!(
+            to_string(&json),
+            "{\"line\u{2028}\":\"a\u{2028}b\u{2029}c\"}"
+        );
+    }
+
+    #[test]
+    fn test_to_string_non_finite() {
+        assert_eq!(to_string(&JsonObject::Number(f64::NAN)), "null");
+        assert_eq!(to_string(&JsonObject::Number(f64::INFINITY)), "null");
+    }
+
+    #[test]
+    fn test_string_from_object() {
+        let json = JsonObject::Array(vec![JsonObject::Boolean(true), JsonObject::Null]);
+        assert_eq!(String::from(&json), "[true,null]");
+        assert_eq!(String::from(&json), to_string(&json));
+    }
+
+    #[test]
+    fn test_display() {
+        let json = JsonObject::Array(vec![JsonObject::Boolean(true), JsonObject::Null]);
+        assert_eq!(format!("{}", json), "[true,null]");
+
+        let json = JsonObject::Array(vec![
+            JsonObject::Number(2.0),
+            JsonObject::Integer(-3),
+            JsonObject::Number(0.25),
+            JsonObject::String("a \"b\"\n".to_string()),
+        ]);
+        assert_eq!(json.to_string(), r#"[2,-3,0.25,"a \"b\"\n"]"#);
+    }
+
+    #[test]
+    fn test_escape_quotes_and_backslashes() {
+        assert_eq!(escape_json_string(r#"say "hi""#), r#"say \"hi\""#);
+        assert_eq!(escape_json_string(r"C:\temp"), r"C:\\temp");
+    }
+
+    #[test]
+    fn test_escape_control_chars() {
+        assert_eq!(escape_json_string("a\nb\tc\r"), r"a\nb\tc\r");
+        assert_eq!(escape_json_string("\u{8}\u{c}\u{1}"), r"\b\f\u0001");
+    }
+
+    #[test]
+    fn test_escape_non_ascii() {
+        assert_eq!(escape_json_string("héllo 😀"), "héllo 😀");
+        assert_eq!(
+            escape_json_string_ascii("héllo 😀"),
+            r"h\u00e9llo \ud83d\ude00"
+        );
+    }
+}
@@ -1,2 +1,5 @@
 pub mod parser;
+pub mod bson;
+pub mod json;
 pub mod message_pack;
+pub mod object;
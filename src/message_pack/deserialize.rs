@@ -0,0 +1,748 @@
+use std::{borrow::Cow, collections::HashMap, str::Utf8Error};
+
+use crate::parser::JsonObject;
+
+/// Upper bound on the number of elements preallocated for a container, so a
+/// header claiming billions of elements cannot trigger a huge allocation.
+/// Larger containers grow as their elements are actually decoded.
+const MAX_PREALLOCATED_ELEMENTS: usize = 4096;
+
+/// How many arrays and maps may be nested inside each other, matching the
+/// default `ParseOptions::max_depth` of the JSON parser. Decoding recurses
+/// once per level, so this keeps hostile input from overflowing the stack.
+const MAX_DEPTH: usize = 128;
+
+#[derive(Debug, PartialEq)]
+pub enum DeserializeError {
+    UnexpectedEof,
+    UnknownFormat(u8),
+    UnsupportedType(&'static str),
+    InvalidKey,
+    InvalidUtf8(Utf8Error),
+    TrailingBytes,
+    /// `deserialize_entries` was given something other than a map.
+    ExpectedMap,
+    /// Arrays and maps are nested deeper than 128 levels.
+    DepthLimitExceeded,
+}
+
+/// Decodes a single MessagePack value from `bytes` into a `JsonObject`.
+///
+/// Integer and boolean map keys are converted to their string form. `bin`
+/// and `ext` values have no `JsonObject` equivalent and are rejected.
+pub fn deserialize(bytes: &[u8]) -> Result<JsonObject, DeserializeError> {
+    let mut reader = Reader { bytes };
+    let object = deserialize_value(&mut reader, 0)?;
+    reader.finish()?;
+    Ok(object)
+}
+
+/// Decodes a top-level MessagePack map into its entries, in the order they
+/// appear on the wire.
+///
+/// `JsonObject::Object` is backed by a `HashMap` and forgets that order;
+/// nested maps are still decoded into it.
+pub fn deserialize_entries(bytes: &[u8]) -> Result<Vec<(String, JsonObject)>, DeserializeError> {
+    let mut reader = Reader { bytes };
+    let Header::Map(len) = reader.read_header()? else {
+        return Err(DeserializeError::ExpectedMap);
+    };
+
+    let mut entries = Vec::with_capacity(len.min(MAX_PREALLOCATED_ELEMENTS));
+    for _ in 0..len {
+        let key = deserialize_key(&mut reader)?;
+        entries.push((key, deserialize_value(&mut reader, 1)?));
+    }
+    reader.finish()?;
+    Ok(entries)
+}
+
+/// A decoded MessagePack value whose strings and binary data borrow from the
+/// input buffer instead of being copied.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonObjectRef<'a> {
+    /// Map entries in wire order, including any repeated keys. Integer and
+    /// boolean keys are converted to owned strings.
+    Object(Vec<(Cow<'a, str>, JsonObjectRef<'a>)>),
+    Array(Vec<JsonObjectRef<'a>>),
+    String(&'a str),
+    Binary(&'a [u8]),
+    Integer(i64),
+    Number(f64),
+    WideInteger(i128),
+    Boolean(bool),
+    Null,
+}
+
+/// Decodes a single MessagePack value from `bytes` without copying strings or
+/// `bin` data. `ext` values are still rejected.
+pub fn deserialize_borrowed(bytes: &[u8]) -> Result<JsonObjectRef<'_>, DeserializeError> {
+    let mut reader = Reader { bytes };
+    let object = deserialize_ref(&mut reader, 0)?;
+    reader.finish()?;
+    Ok(object)
+}
+
+fn deserialize_ref<'a>(
+    reader: &mut Reader<'a>,
+    depth: usize,
+) -> Result<JsonObjectRef<'a>, DeserializeError> {
+    let object = match reader.read_header()? {
+        Header::Array(_) | Header::Map(_) if depth >= MAX_DEPTH => {
+            return Err(DeserializeError::DepthLimitExceeded);
+        }
+        Header::Nil => JsonObjectRef::Null,
+        Header::Bool(value) => JsonObjectRef::Boolean(value),
+        Header::Uint(value) => match i64::try_from(value) {
+            Ok(value) => JsonObjectRef::Integer(value),
+            Err(_) => JsonObjectRef::WideInteger(value.into()),
+        },
+        Header::Int(value) => JsonObjectRef::Integer(value),
+        Header::Float(value) => JsonObjectRef::Number(value),
+        Header::Str(value) => JsonObjectRef::String(value),
+        Header::Bin(value) => JsonObjectRef::Binary(value),
+        Header::Ext(_, _) => return Err(DeserializeError::UnsupportedType("ext")),
+        Header::Array(len) => {
+            let mut elements = Vec::with_capacity(len.min(MAX_PREALLOCATED_ELEMENTS));
+            for _ in 0..len {
+                elements.push(deserialize_ref(reader, depth + 1)?);
+            }
+            JsonObjectRef::Array(elements)
+        }
+        Header::Map(len) => {
+            let mut entries = Vec::with_capacity(len.min(MAX_PREALLOCATED_ELEMENTS));
+            for _ in 0..len {
+                let key = match reader.read_header()? {
+                    Header::Str(value) => Cow::Borrowed(value),
+                    Header::Uint(value) => Cow::Owned(value.to_string()),
+                    Header::Int(value) => Cow::Owned(value.to_string()),
+                    Header::Bool(value) => Cow::Owned(value.to_string()),
+                    _ => return Err(DeserializeError::InvalidKey),
+                };
+                entries.push((key, deserialize_ref(reader, depth + 1)?));
+            }
+            JsonObjectRef::Object(entries)
+        }
+    };
+
+    Ok(object)
+}
+
+fn deserialize_value(reader: &mut Reader, depth: usize) -> Result<JsonObject, DeserializeError> {
+    let object = match reader.read_header()? {
+        Header::Array(_) | Header::Map(_) if depth >= MAX_DEPTH => {
+            return Err(DeserializeError::DepthLimitExceeded);
+        }
+        Header::Nil => JsonObject::Null,
+        Header::Bool(value) => JsonObject::Boolean(value),
+        Header::Uint(value) => match i64::try_from(value) {
+            Ok(value) => JsonObject::Integer(value),
+            Err(_) => JsonObject::WideInteger(value.into()),
+        },
+        Header::Int(value) => JsonObject::Integer(value),
+        Header::Float(value) => JsonObject::Number(value),
+        Header::Str(value) => JsonObject::String(value.to_string()),
+        Header::Bin(_) => return Err(DeserializeError::UnsupportedType("bin")),
+        Header::Ext(_, _) => return Err(DeserializeError::UnsupportedType("ext")),
+        Header::Array(len) => {
+            let mut elements = Vec::with_capacity(len.min(MAX_PREALLOCATED_ELEMENTS));
+            for _ in 0..len {
+                elements.push(deserialize_value(reader, depth + 1)?);
+            }
+            JsonObject::Array(elements)
+        }
+        Header::Map(len) => {
+            let mut elements = HashMap::with_capacity(len.min(MAX_PREALLOCATED_ELEMENTS));
+            for _ in 0..len {
+                let key = deserialize_key(reader)?;
+                elements.insert(key, deserialize_value(reader, depth + 1)?);
+            }
+            JsonObject::Object(elements)
+        }
+    };
+
+    Ok(object)
+}
+
+fn deserialize_key(reader: &mut Reader) -> Result<String, DeserializeError> {
+    match reader.read_header()? {
+        Header::Str(value) => Ok(value.to_string()),
+        Header::Uint(value) => Ok(value.to_string()),
+        Header::Int(value) => Ok(value.to_string()),
+        Header::Bool(value) => Ok(value.to_string()),
+        _ => Err(DeserializeError::InvalidKey),
+    }
+}
+
+/// Receives MessagePack values as events in wire order. Map entries are
+/// reported as alternating key and value events between the start and end.
+pub trait Visitor {
+    fn on_nil(&mut self) {}
+    fn on_bool(&mut self, _value: bool) {}
+    fn on_uint(&mut self, _value: u64) {}
+    fn on_int(&mut self, _value: i64) {}
+    fn on_float(&mut self, _value: f64) {}
+    fn on_str(&mut self, _value: &str) {}
+    fn on_bin(&mut self, _value: &[u8]) {}
+    fn on_ext(&mut self, _type_id: i8, _data: &[u8]) {}
+    fn on_array_start(&mut self, _len: usize) {}
+    fn on_array_end(&mut self) {}
+    fn on_map_start(&mut self, _len: usize) {}
+    fn on_map_end(&mut self) {}
+}
+
+/// Decodes a single MessagePack value from `bytes`, reporting it to `visitor`
+/// without building a tree.
+pub fn visit<V: Visitor>(bytes: &[u8], visitor: &mut V) -> Result<(), DeserializeError> {
+    let mut reader = Reader { bytes };
+    visit_value(&mut reader, visitor, 0)?;
+    reader.finish()
+}
+
+fn visit_value<V: Visitor>(
+    reader: &mut Reader,
+    visitor: &mut V,
+    depth: usize,
+) -> Result<(), DeserializeError> {
+    match reader.read_header()? {
+        Header::Array(_) | Header::Map(_) if depth >= MAX_DEPTH => {
+            return Err(DeserializeError::DepthLimitExceeded);
+        }
+        Header::Nil => visitor.on_nil(),
+        Header::Bool(value) => visitor.on_bool(value),
+        Header::Uint(value) => visitor.on_uint(value),
+        Header::Int(value) => visitor.on_int(value),
+        Header::Float(value) => visitor.on_float(value),
+        Header::Str(value) => visitor.on_str(value),
+        Header::Bin(value) => visitor.on_bin(value),
+        Header::Ext(type_id, data) => visitor.on_ext(type_id, data),
+        Header::Array(len) => {
+            visitor.on_array_start(len);
+            for _ in 0..len {
+                visit_value(reader, visitor, depth + 1)?;
+            }
+            visitor.on_array_end();
+        }
+        Header::Map(len) => {
+            visitor.on_map_start(len);
+            for _ in 0..len {
+                visit_value(reader, visitor, depth + 1)?;
+                visit_value(reader, visitor, depth + 1)?;
+            }
+            visitor.on_map_end();
+        }
+    }
+
+    Ok(())
+}
+
+enum Header<'a> {
+    Nil,
+    Bool(bool),
+    Uint(u64),
+    Int(i64),
+    Float(f64),
+    Str(&'a str),
+    Bin(&'a [u8]),
+    Ext(i8, &'a [u8]),
+    Array(usize),
+    Map(usize),
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn finish(&self) -> Result<(), DeserializeError> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(DeserializeError::TrailingBytes)
+        }
+    }
+
+    fn read_header(&mut self) -> Result<Header<'a>, DeserializeError> {
+        let format = self.read_u8()?;
+
+        let header = match format {
+            0x00..=0x7f => Header::Uint(format.into()),
+            0x80..=0x8f => Header::Map((format & 0x0f).into()),
+            0x90..=0x9f => Header::Array((format & 0x0f).into()),
+            0xa0..=0xbf => self.read_str((format & 0x1f).into())?,
+            0xc0 => Header::Nil,
+            0xc2 => Header::Bool(false),
+            0xc3 => Header::Bool(true),
+            0xc4 => {
+                let len = self.read_u8()?.into();
+                Header::Bin(self.read_slice(len)?)
+            }
+            0xc5 => {
+                let len = u16::from_be_bytes(self.read_array()?).into();
+                Header::Bin(self.read_slice(len)?)
+            }
+            0xc6 => {
+                let len = u32::from_be_bytes(self.read_array()?) as usize;
+                Header::Bin(self.read_slice(len)?)
+            }
+            0xc7 => {
+                let len = self.read_u8()?.into();
+                self.read_ext(len)?
+            }
+            0xc8 => {
+                let len = u16::from_be_bytes(self.read_array()?).into();
+                self.read_ext(len)?
+            }
+            0xc9 => {
+                let len = u32::from_be_bytes(self.read_array()?) as usize;
+                self.read_ext(len)?
+            }
+            0xca => Header::Float(f32::from_be_bytes(self.read_array()?).into()),
+            0xcb => Header::Float(f64::from_be_bytes(self.read_array()?)),
+            0xcc => Header::Uint(self.read_u8()?.into()),
+            0xcd => Header::Uint(u16::from_be_bytes(self.read_array()?).into()),
+            0xce => Header::Uint(u32::from_be_bytes(self.read_array()?).into()),
+            0xcf => Header::Uint(u64::from_be_bytes(self.read_array()?)),
+            0xd0 => Header::Int(i8::from_be_bytes(self.read_array()?).into()),
+            0xd1 => Header::Int(i16::from_be_bytes(self.read_array()?).into()),
+            0xd2 => Header::Int(i32::from_be_bytes(self.read_array()?).into()),
+            0xd3 => Header::Int(i64::from_be_bytes(self.read_array()?)),
+            0xd4 => self.read_ext(1)?,
+            0xd5 => self.read_ext(2)?,
+            0xd6 => self.read_ext(4)?,
+            0xd7 => self.read_ext(8)?,
+            0xd8 => self.read_ext(16)?,
+            0xd9 => {
+                let len = self.read_u8()?.into();
+                self.read_str(len)?
+            }
+            0xda => {
+                let len = u16::from_be_bytes(self.read_array()?).into();
+                self.read_str(len)?
+            }
+            0xdb => {
+                let len = u32::from_be_bytes(self.read_array()?) as usize;
+                self.read_str(len)?
+            }
+            0xdc => Header::Array(u16::from_be_bytes(self.read_array()?).into()),
+            0xdd => Header::Array(u32::from_be_bytes(self.read_array()?) as usize),
+            0xde => Header::Map(u16::from_be_bytes(self.read_array()?).into()),
+            0xdf => Header::Map(u32::from_be_bytes(self.read_array()?) as usize),
+            0xe0..=0xff => Header::Int((format as i8).into()),
+            _ => return Err(DeserializeError::UnknownFormat(format)),
+        };
+
+        // Every element takes at least one byte, so a container claiming more
+        // elements than there are bytes left is necessarily truncated.
+        match header {
+            Header::Array(len) if len > self.bytes.len() => Err(DeserializeError::UnexpectedEof),
+            Header::Map(len) if len.saturating_mul(2) > self.bytes.len() => {
+                Err(DeserializeError::UnexpectedEof)
+            }
+            _ => Ok(header),
+        }
+    }
+
+    fn read_str(&mut self, len: usize) -> Result<Header<'a>, DeserializeError> {
+        let bytes = self.read_slice(len)?;
+        let value = std::str::from_utf8(bytes).map_err(DeserializeError::InvalidUtf8)?;
+        Ok(Header::Str(value))
+    }
+
+    fn read_ext(&mut self, len: usize) -> Result<Header<'a>, DeserializeError> {
+        let [type_id] = self.read_array()?;
+        Ok(Header::Ext(type_id as i8, self.read_slice(len)?))
+    }
+
+    fn read_u8(&mut self) -> Result<u8, DeserializeError> {
+        let [byte] = self.read_array()?;
+        Ok(byte)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], DeserializeError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.read_slice(N)?);
+        Ok(array)
+    }
+
+    fn read_slice(&mut self, len: usize) -> Result<&'a [u8], DeserializeError> {
+        if len > self.bytes.len() {
+            return Err(DeserializeError::UnexpectedEof);
+        }
+
+        let (slice, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{message_pack::serialize::try_serialize, parser::from_str};
+
+    #[derive(Default)]
+    struct IntCounter {
+        count: usize,
+    }
+
+    impl Visitor for IntCounter {
+        fn on_uint(&mut self, _value: u64) {
+            self.count += 1;
+        }
+
+        fn on_int(&mut self, _value: i64) {
+            self.count += 1;
+        }
+    }
+
+    #[derive(Default)]
+    struct EventRecorder {
+        events: Vec<String>,
+    }
+
+    impl Visitor for EventRecorder {
+        fn on_nil(&mut self) {
+            self.events.push("nil".to_string());
+        }
+
+        fn on_uint(&mut self, value: u64) {
+            self.events.push(format!("uint {}", value));
+        }
+
+        fn on_int(&mut self, value: i64) {
+            self.events.push(format!("int {}", value));
+        }
+
+        fn on_str(&mut self, value: &str) {
+            self.events.push(format!("str {}", value));
+        }
+
+        fn on_array_start(&mut self, len: usize) {
+            self.events.push(format!("array_start {}", len));
+        }
+
+        fn on_array_end(&mut self) {
+            self.events.push("array_end".to_string());
+        }
+
+        fn on_map_start(&mut self, len: usize) {
+            self.events.push(format!("map_start {}", len));
+        }
+
+        fn on_map_end(&mut self) {
+            self.events.push("map_end".to_string());
+        }
+    }
+
+    #[test]
+    fn test_visit_counts_integers() {
+        // [1, "a", 300, -1, nil, 0.5]
+        let bytes = [
+            0x96, 0x01, 0xa1, b'a', 0xcd, 0x01, 0x2c, 0xff, 0xc0, 0xcb, 0x3f, 0xe0, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let mut counter = IntCounter::default();
+        visit(&bytes, &mut counter).unwrap();
+
+        assert_eq!(counter.count, 3);
+    }
+
+    #[test]
+    fn test_visit_reports_container_events() {
+        // {"a": [nil, 7]}
+        let bytes = [0x81, 0xa1, b'a', 0x92, 0xc0, 0x07];
+
+        let mut recorder = EventRecorder::default();
+        visit(&bytes, &mut recorder).unwrap();
+
+        assert_eq!(
+            recorder.events,
+            vec![
+                "map_start 1",
+                "str a",
+                "array_start 2",
+                "nil",
+                "uint 7",
+                "array_end",
+                "map_end",
+            ]
+        );
+    }
+
+    fn record(bytes: &[u8]) -> Vec<String> {
+        let mut recorder = EventRecorder::default();
+        visit(bytes, &mut recorder).unwrap();
+        recorder.events
+    }
+
+    #[test]
+    fn test_deserialize_scalars() {
+        assert_eq!(deserialize(&[0xc0]), Ok(JsonObject::Null));
+        assert_eq!(deserialize(&[0xc3]), Ok(JsonObject::Boolean(true)));
+        assert_eq!(deserialize(&[0xd0, 0x80]), Ok(JsonObject::Number(-128.0)));
+        assert_eq!(
+            deserialize(&[0xa3, b'f', b'o', b'o']),
+            Ok(JsonObject::String("foo".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_deserialize_floats() {
+        let mut float32 = vec![0xca];
+        float32.extend(1.5f32.to_be_bytes());
+        assert_eq!(deserialize(&float32), Ok(JsonObject::Number(1.5)));
+
+        let mut float64 = vec![0xcb];
+        float64.extend(0.1f64.to_be_bytes());
+        assert_eq!(deserialize(&float64), Ok(JsonObject::Number(0.1)));
+
+        // float32 is widened, so it keeps single-precision rounding.
+        let mut float32 = vec![0xca];
+        float32.extend(0.1f32.to_be_bytes());
+        assert_eq!(deserialize(&float32), Ok(JsonObject::Number(0.1f32.into())));
+
+        assert_eq!(
+            deserialize(&[0xca, 0x3f, 0xc0, 0x00]),
+            Err(DeserializeError::UnexpectedEof)
+        );
+    }
+
+    #[test]
+    fn test_deserialize_nil_in_containers() {
+        assert_eq!(
+            deserialize(&[0x92, 0xc0, 0x01]),
+            Ok(JsonObject::Array(vec![
+                JsonObject::Null,
+                JsonObject::Number(1.0)
+            ]))
+        );
+
+        let mut map = HashMap::new();
+        map.insert("a".to_string(), JsonObject::Null);
+        map.insert("b".to_string(), JsonObject::Number(1.0));
+        assert_eq!(
+            deserialize(&[0x82, 0xa1, b'a', 0xc0, 0xa1, b'b', 0x01]),
+            Ok(JsonObject::Object(map))
+        );
+    }
+
+    #[test]
+    fn test_deserialize_map_keys() {
+        let mut map = HashMap::new();
+        map.insert("7".to_string(), JsonObject::Boolean(false));
+        assert_eq!(
+            deserialize(&[0x81, 0x07, 0xc2]),
+            Ok(JsonObject::Object(map))
+        );
+
+        assert_eq!(
+            deserialize(&[0x81, 0x90, 0xc2]),
+            Err(DeserializeError::InvalidKey)
+        );
+    }
+
+    #[test]
+    fn test_deserialize_entries_keeps_wire_order() {
+        // {"z": 1, "a": {"k": nil}, "m": true}
+        let bytes = [
+            0x83, 0xa1, b'z', 0x01, 0xa1, b'a', 0x81, 0xa1, b'k', 0xc0, 0xa1, b'm', 0xc3,
+        ];
+
+        let entries = deserialize_entries(&bytes).unwrap();
+        let keys: Vec<_> = entries.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, ["z", "a", "m"]);
+        assert_eq!(
+            entries[1].1,
+            JsonObject::Object(HashMap::from([("k".to_string(), JsonObject::Null)]))
+        );
+
+        assert_eq!(
+            deserialize_entries(&[0x90]),
+            Err(DeserializeError::ExpectedMap)
+        );
+        assert_eq!(
+            deserialize_entries(&[0x80, 0xc0]),
+            Err(DeserializeError::TrailingBytes)
+        );
+    }
+
+    #[test]
+    fn test_deserialize_round_trips_serialized_values() {
+        let documents = [
+            "null",
+            "[true, false, 0, -1, -129, 300, 4294967296, -2.5, 0.1]",
+            r#"{"name": "café", "tags": [], "nested": {"a": [null, {}]}}"#,
+        ];
+
+        for input in documents {
+            let json = from_str(input).unwrap();
+            assert_eq!(
+                deserialize(&try_serialize(&json).unwrap()),
+                Ok(json),
+                "{}",
+                input
+            );
+        }
+
+        let long = JsonObject::Array(vec![JsonObject::String("x".repeat(300)); 20]);
+        assert_eq!(deserialize(&try_serialize(&long).unwrap()), Ok(long));
+    }
+
+    #[test]
+    fn test_deserialize_unknown_format() {
+        assert_eq!(
+            deserialize(&[0xc1]),
+            Err(DeserializeError::UnknownFormat(0xc1))
+        );
+    }
+
+    #[test]
+    fn test_deserialize_borrowed_strings() {
+        let bytes = [
+            &[0x82, 0xa4][..],
+            b"name",
+            &[0xa5],
+            b"hello",
+            &[0x07, 0x92, 0xc4, 0x02, 0xde, 0xad, 0xc3],
+        ]
+        .concat();
+        let json = deserialize_borrowed(&bytes).unwrap();
+
+        let JsonObjectRef::Object(entries) = &json else {
+            panic!("expected a map, got {:?}", json);
+        };
+        let (Cow::Borrowed(key), JsonObjectRef::String(value)) = (&entries[0].0, &entries[0].1)
+        else {
+            panic!("expected borrowed strings, got {:?}", entries[0]);
+        };
+        let source = bytes.as_ptr_range();
+        assert!(source.contains(&key.as_ptr()));
+        assert_eq!(value.as_ptr(), bytes[7..].as_ptr());
+        assert_eq!(*value, "hello");
+
+        assert_eq!(
+            entries[1],
+            (
+                Cow::Owned("7".to_string()),
+                JsonObjectRef::Array(vec![
+                    JsonObjectRef::Binary(&[0xde, 0xad]),
+                    JsonObjectRef::Boolean(true)
+                ])
+            )
+        );
+        assert_eq!(
+            deserialize_borrowed(&[0xa3, b'a']),
+            Err(DeserializeError::UnexpectedEof)
+        );
+    }
+
+    #[test]
+    fn test_deserialize_truncated_input() {
+        let truncated: [&[u8]; 6] = [
+            &[0xd9, 0x0a, b'a', b'b', b'c'],
+            &[0xc4, 0x04, 0x00],
+            &[0xcb, 0x3f, 0xf0],
+            &[0xdc, 0x00, 0x05, 0xc0],
+            &[0x82, 0xa1, b'a', 0xc0],
+            &[0x92, 0xc0],
+        ];
+
+        for bytes in truncated {
+            assert_eq!(deserialize(bytes), Err(DeserializeError::UnexpectedEof));
+        }
+    }
+
+    #[test]
+    fn test_deserialize_bogus_container_lengths() {
+        let mut bytes = vec![0xdd, 0xff, 0xff, 0xff, 0xff];
+        bytes.extend([0xc0; 16]);
+        assert_eq!(deserialize(&bytes), Err(DeserializeError::UnexpectedEof));
+
+        let mut bytes = vec![0xdf, 0x7f, 0xff, 0xff, 0xff];
+        bytes.extend([0xc0; 16]);
+        assert_eq!(deserialize(&bytes), Err(DeserializeError::UnexpectedEof));
+
+        // Enough bytes to pass the header check, but the elements run short.
+        let mut bytes = vec![0xdd, 0x00, 0x01, 0x00, 0x00];
+        bytes.extend([0xcd; 65536]);
+        assert_eq!(deserialize(&bytes), Err(DeserializeError::UnexpectedEof));
+    }
+
+    #[test]
+    fn test_deserialize_depth_limit() {
+        let mut bytes = vec![0x91; 1_000_000];
+        bytes.push(0xc0);
+        assert_eq!(
+            deserialize(&bytes),
+            Err(DeserializeError::DepthLimitExceeded)
+        );
+        assert_eq!(
+            deserialize_borrowed(&bytes),
+            Err(DeserializeError::DepthLimitExceeded)
+        );
+        assert_eq!(
+            visit(&bytes, &mut IntCounter::default()),
+            Err(DeserializeError::DepthLimitExceeded)
+        );
+
+        let mut maps = [0x81, 0xa1, b'k'].repeat(200);
+        maps.push(0xc0);
+        assert_eq!(
+            deserialize(&maps),
+            Err(DeserializeError::DepthLimitExceeded)
+        );
+        assert_eq!(
+            deserialize_entries(&maps),
+            Err(DeserializeError::DepthLimitExceeded)
+        );
+
+        let mut nested = vec![0x91; 128];
+        nested.push(0xc0);
+        assert!(deserialize(&nested).is_ok());
+        assert!(deserialize_borrowed(&nested).is_ok());
+        nested.insert(0, 0x91);
+        assert_eq!(
+            deserialize(&nested),
+            Err(DeserializeError::DepthLimitExceeded)
+        );
+    }
+
+    #[test]
+    fn test_deserialize_large_array() {
+        let mut bytes = vec![0xdd, 0x00, 0x01, 0x00, 0x00];
+        bytes.extend([0x01; 65536]);
+
+        let JsonObject::Array(elements) = deserialize(&bytes).unwrap() else {
+            panic!("Expected array");
+        };
+        assert_eq!(elements.len(), 65536);
+    }
+
+    #[test]
+    fn test_visit_fixint_boundaries() {
+        assert_eq!(record(&[0x00]), vec!["uint 0"]);
+        assert_eq!(record(&[0x7f]), vec!["uint 127"]);
+        assert_eq!(record(&[0xff]), vec!["int -1"]);
+        assert_eq!(record(&[0xe0]), vec!["int -32"]);
+    }
+
+    #[test]
+    fn test_visit_errors() {
+        let mut counter = IntCounter::default();
+
+        assert_eq!(
+            visit(&[], &mut counter),
+            Err(DeserializeError::UnexpectedEof)
+        );
+        assert_eq!(
+            visit(&[0xc1], &mut counter),
+            Err(DeserializeError::UnknownFormat(0xc1))
+        );
+        assert_eq!(
+            visit(&[0xc0, 0xc0], &mut counter),
+            Err(DeserializeError::TrailingBytes)
+        );
+    }
+}
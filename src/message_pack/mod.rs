@@ -1 +1,3 @@
+pub mod deserialize;
 pub mod serialize;
+pub mod transcode;
@@ -1,28 +1,280 @@
 use crate::parser::JsonObject;
 
+#[derive(Debug, PartialEq)]
+pub enum SerializeError {
+    /// A string, array or map is longer than the `u32::MAX` elements
+    /// MessagePack can describe.
+    TooLong(usize),
+    /// An integral number has a magnitude of at least 2^53, where `f64` can no
+    /// longer tell neighbouring integers apart, so it may not be the value
+    /// that was originally parsed. Only reported by `serialize_checked`.
+    PrecisionLoss(f64),
+    /// MessagePack integers are at most 64 bits wide, so only the part of the
+    /// `i128` range from `i64::MIN` to `u64::MAX` can be encoded.
+    IntegerTooWide(i128),
+}
+
+/// The largest integer `n` for which `n` and `n + 1` are both exactly
+/// representable as `f64`.
+const MAX_SAFE_INTEGER: f64 = 9007199254740991.0;
+
+/// Encodes `object` as MessagePack.
+///
+/// Map keys are written in the object's iteration order, which is
+/// unspecified for `HashMap`-backed objects. Use `serialize_sorted` when the
+/// output must be deterministic.
+///
+/// Panics if a string or container is too long to encode, or if a
+/// `WideInteger` (from `ParseOptions::wide_integers`) is outside the 64-bit
+/// ranges.
+#[deprecated(note = "panics on values MessagePack cannot encode; use `try_serialize`")]
 pub fn serialize(object: JsonObject) -> Vec<u8> {
+    try_serialize(&object).unwrap_or_else(|error| panic!("Cannot encode value: {:?}", error))
+}
+
+pub fn try_serialize(object: &JsonObject) -> Result<Vec<u8>, SerializeError> {
+    let mut output = vec![];
+    write_value(&mut output, object, WriteOptions::default())?;
+    Ok(output)
+}
+
+/// Like `try_serialize`, but writes map keys in sorted order, so equal
+/// values always encode to the same bytes.
+pub fn serialize_sorted(object: &JsonObject) -> Result<Vec<u8>, SerializeError> {
+    let options = WriteOptions {
+        sorted_keys: true,
+        ..Default::default()
+    };
+    let mut output = vec![];
+    write_value(&mut output, object, options)?;
+    Ok(output)
+}
+
+/// Like `try_serialize`, but also fails with `SerializeError::PrecisionLoss`
+/// for integral numbers too large to be stored exactly.
+pub fn serialize_checked(object: &JsonObject) -> Result<Vec<u8>, SerializeError> {
+    let options = WriteOptions {
+        checked: true,
+        ..Default::default()
+    };
+    let mut output = vec![];
+    write_value(&mut output, object, options)?;
+    Ok(output)
+}
+
+/// Like `try_serialize`, but writes map keys that are the canonical form of
+/// an `i64`, such as `"7"` or `"-12"`, as MessagePack integers. Keys like
+/// `"07"` or `"+7"` stay strings so they decode back unchanged.
+pub fn serialize_integer_keys(object: &JsonObject) -> Result<Vec<u8>, SerializeError> {
+    let options = WriteOptions {
+        integer_keys: true,
+        ..Default::default()
+    };
+    let mut output = vec![];
+    write_value(&mut output, object, options)?;
+    Ok(output)
+}
+
+/// Like `try_serialize`, but encodes `String` values in the bin family
+/// instead of str, for peers that predate the str8 format. Map keys are
+/// still written as str.
+pub fn serialize_bin_strings(object: &JsonObject) -> Result<Vec<u8>, SerializeError> {
+    let options = WriteOptions {
+        bin_strings: true,
+        ..Default::default()
+    };
+    let mut output = vec![];
+    write_value(&mut output, object, options)?;
+    Ok(output)
+}
+
+impl TryFrom<JsonObject> for Vec<u8> {
+    type Error = SerializeError;
+
+    fn try_from(object: JsonObject) -> Result<Self, Self::Error> {
+        try_serialize(&object)
+    }
+}
+
+/// Encodes values into a buffer that is kept between calls, so repeated
+/// encoding does not allocate a new output each time.
+#[derive(Debug, Default)]
+pub struct Serializer {
+    buffer: Vec<u8>,
+}
+
+impl Serializer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Encodes `object`, replacing the result of the previous call.
+    pub fn serialize_reuse(&mut self, object: &JsonObject) -> Result<&[u8], SerializeError> {
+        self.buffer.clear();
+        write_value(&mut self.buffer, object, WriteOptions::default())?;
+        Ok(&self.buffer)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct WriteOptions {
+    /// Report integral numbers too large to be stored exactly.
+    checked: bool,
+    /// Encode strings as bin instead of str.
+    bin_strings: bool,
+    sorted_keys: bool,
+    /// Write integer-like map keys as integers.
+    integer_keys: bool,
+}
+
+fn write_value(
+    output: &mut Vec<u8>,
+    object: &JsonObject,
+    options: WriteOptions,
+) -> Result<(), SerializeError> {
     match object {
-        JsonObject::Null => vec![0xc0],
-        JsonObject::Boolean(false) => vec![0xc2],
-        JsonObject::Boolean(true) => vec![0xc3],
-        JsonObject::Number(val) => {
-            if val == val.trunc() {
-                if val < 0.0 {
-                    serialize_int(val)
-                } else {
-                    serialize_uint(val as u64)
+        JsonObject::Null => output.push(0xc0),
+        JsonObject::Boolean(false) => output.push(0xc2),
+        JsonObject::Boolean(true) => output.push(0xc3),
+        JsonObject::Number(val)
+            if options.checked && *val == val.trunc() && val.abs() > MAX_SAFE_INTEGER =>
+        {
+            return Err(SerializeError::PrecisionLoss(*val));
+        }
+        JsonObject::Integer(val) => output.extend(encode_int(*val)),
+        JsonObject::Number(val) => output.extend(serialize_number(*val)),
+        JsonObject::WideInteger(val) => match (i64::try_from(*val), u64::try_from(*val)) {
+            (Ok(val), _) => output.extend(encode_int(val)),
+            (_, Ok(val)) => output.extend(encode_uint(val)),
+            _ => return Err(SerializeError::IntegerTooWide(*val)),
+        },
+        JsonObject::String(val) if options.bin_strings => {
+            check_len(val.len())?;
+            write_bin(output, val.as_bytes());
+        }
+        JsonObject::String(val) => {
+            check_len(val.len())?;
+            write_str(output, val);
+        }
+        JsonObject::Array(elements) => {
+            check_len(elements.len())?;
+            output.extend(serialize_array_header(elements.len()));
+            for element in elements {
+                write_value(output, element, options)?;
+            }
+        }
+        JsonObject::Object(elements) => {
+            check_len(elements.len())?;
+            output.extend(serialize_map_header(elements.len()));
+
+            let mut entries: Vec<_> = elements.iter().collect();
+            if options.sorted_keys {
+                entries.sort_by_key(|(key, _)| *key);
+            }
+            for (key, value) in entries {
+                match key.parse::<i64>() {
+                    Ok(int) if options.integer_keys && int.to_string() == *key => {
+                        output.extend(encode_int(int));
+                    }
+                    _ => {
+                        check_len(key.len())?;
+                        write_str(output, key);
+                    }
                 }
-            } else {
-                todo!()
+                write_value(output, value, options)?;
             }
         }
-        _ => todo!(),
     }
+
+    Ok(())
+}
+
+fn check_len(len: usize) -> Result<(), SerializeError> {
+    match u32::try_from(len) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(SerializeError::TooLong(len)),
+    }
+}
+
+/// Encodes integral values in the `i64` and `u64` ranges as integers, and
+/// everything else as float32 when that is lossless or float64 otherwise.
+/// `-0.0` is kept as a float so its sign survives.
+pub(crate) fn serialize_number(val: f64) -> Vec<u8> {
+    // `u64::MAX as f64` rounds up to 2^64, which is itself out of range.
+    let in_range = val >= i64::MIN as f64 && val < u64::MAX as f64;
+    let negative_zero = val == 0.0 && val.is_sign_negative();
+
+    if val == val.trunc() && in_range && !negative_zero {
+        if val < 0.0 {
+            encode_int(val as i64)
+        } else {
+            encode_uint(val as u64)
+        }
+    } else if (val as f32) as f64 == val || val.is_nan() {
+        let mut output = (val as f32).to_be_bytes().to_vec();
+        output.insert(0, 0xca);
+        output
+    } else {
+        let mut output = val.to_be_bytes().to_vec();
+        output.insert(0, 0xcb);
+        output
+    }
+}
+
+pub(crate) fn serialize_str(val: &str) -> Vec<u8> {
+    let mut output = vec![];
+    write_str(&mut output, val);
+    output
+}
+
+fn write_str(output: &mut Vec<u8>, val: &str) {
+    match val.len() {
+        len if len < 32 => output.push(0xa0 | len as u8),
+        len if len <= u8::MAX.into() => output.extend([0xd9, len as u8]),
+        len => output.extend(serialize_len(0xda, 0xdb, len)),
+    }
+    output.extend(val.as_bytes());
 }
 
-fn serialize_uint(val: u64) -> Vec<u8> {
+fn write_bin(output: &mut Vec<u8>, val: &[u8]) {
+    match val.len() {
+        len if len <= u8::MAX.into() => output.extend([0xc4, len as u8]),
+        len => output.extend(serialize_len(0xc5, 0xc6, len)),
+    }
+    output.extend(val);
+}
+
+pub(crate) fn serialize_array_header(len: usize) -> Vec<u8> {
+    match len {
+        len if len < 16 => vec![0x90 | len as u8],
+        len => serialize_len(0xdc, 0xdd, len),
+    }
+}
+
+pub(crate) fn serialize_map_header(len: usize) -> Vec<u8> {
+    match len {
+        len if len < 16 => vec![0x80 | len as u8],
+        len => serialize_len(0xde, 0xdf, len),
+    }
+}
+
+fn serialize_len(marker16: u8, marker32: u8, len: usize) -> Vec<u8> {
+    if len <= u16::MAX.into() {
+        let mut val = (len as u16).to_be_bytes().to_vec();
+        val.insert(0, marker16);
+        val
+    } else {
+        let len = u32::try_from(len).expect("MessagePack lengths cannot exceed u32::MAX");
+        let mut val = len.to_be_bytes().to_vec();
+        val.insert(0, marker32);
+        val
+    }
+}
+
+/// Encodes `val` in the smallest unsigned MessagePack integer format.
+pub(crate) fn encode_uint(val: u64) -> Vec<u8> {
     match val {
-        val if val <= (1 << 7) - 1 => vec![val as u8],
+        val if val < 1 << 7 => vec![val as u8],
         val if val <= u8::MAX.into() => vec![0xcc, val as u8],
         val if val <= u16::MAX.into() => {
             let mut val = (val as u16).to_be_bytes().to_vec();
@@ -42,13 +294,41 @@ fn serialize_uint(val: u64) -> Vec<u8> {
     }
 }
 
-fn serialize_int(val: f64) -> Vec<u8> {
-    todo!()
+/// Encodes `val` in the smallest MessagePack integer format. Non-negative
+/// values use the unsigned formats.
+pub(crate) fn encode_int(val: i64) -> Vec<u8> {
+    match val {
+        val if val >= 0 => encode_uint(val as u64),
+        val if val >= -32 => vec![val as u8],
+        val if val >= i8::MIN.into() => vec![0xd0, val as u8],
+        val if val >= i16::MIN.into() => {
+            let mut val = (val as i16).to_be_bytes().to_vec();
+            val.insert(0, 0xd1);
+            val
+        }
+        val if val >= i32::MIN.into() => {
+            let mut val = (val as i32).to_be_bytes().to_vec();
+            val.insert(0, 0xd2);
+            val
+        }
+        _ => {
+            let mut val = val.to_be_bytes().to_vec();
+            val.insert(0, 0xd3);
+            val
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+
     use super::*;
+    use crate::parser::from_str;
+
+    fn serialize(object: JsonObject) -> Vec<u8> {
+        try_serialize(&object).unwrap()
+    }
 
     #[test]
     fn test_serialize_null() {
@@ -77,9 +357,403 @@ mod tests {
             serialize(JsonObject::Number(4294967295.0)),
             vec![0xce, 0xff, 0xff, 0xff, 0xff]
         );
+        assert_eq!(
+            serialize(JsonObject::Number(18446744073709549568.0)),
+            vec![0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xf8, 0x00]
+        );
+        // `u64::MAX as f64` is 2^64, one past the largest u64.
         assert_eq!(
             serialize(JsonObject::Number(u64::MAX as f64)),
-            vec![0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+            vec![0xca, 0x5f, 0x80, 0x00, 0x00]
+        );
+    }
+
+    #[test]
+    fn test_encode_uint_boundaries() {
+        assert_eq!(encode_uint(0), vec![0x00]);
+        assert_eq!(encode_uint(127), vec![0x7f]);
+        assert_eq!(encode_uint(128), vec![0xcc, 0x80]);
+        assert_eq!(encode_uint(255), vec![0xcc, 0xff]);
+        assert_eq!(encode_uint(256), vec![0xcd, 0x01, 0x00]);
+        assert_eq!(encode_uint(65535), vec![0xcd, 0xff, 0xff]);
+        assert_eq!(encode_uint(65536), vec![0xce, 0x00, 0x01, 0x00, 0x00]);
+        assert_eq!(
+            encode_uint(u32::MAX.into()),
+            vec![0xce, 0xff, 0xff, 0xff, 0xff]
         );
+        assert_eq!(
+            encode_uint(u64::from(u32::MAX) + 1),
+            vec![0xcf, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00]
+        );
+    }
+
+    #[test]
+    fn test_encode_int_boundaries() {
+        assert_eq!(encode_int(5), vec![0x05]);
+        assert_eq!(encode_int(-1), vec![0xff]);
+        assert_eq!(encode_int(-32), vec![0xe0]);
+        assert_eq!(encode_int(-33), vec![0xd0, 0xdf]);
+        assert_eq!(encode_int(-128), vec![0xd0, 0x80]);
+        assert_eq!(encode_int(-129), vec![0xd1, 0xff, 0x7f]);
+        assert_eq!(encode_int(-32768), vec![0xd1, 0x80, 0x00]);
+        assert_eq!(encode_int(-32769), vec![0xd2, 0xff, 0xff, 0x7f, 0xff]);
+        assert_eq!(
+            encode_int(i32::MIN.into()),
+            vec![0xd2, 0x80, 0x00, 0x00, 0x00]
+        );
+        assert_eq!(
+            encode_int(i64::from(i32::MIN) - 1),
+            vec![0xd3, 0xff, 0xff, 0xff, 0xff, 0x7f, 0xff, 0xff, 0xff]
+        );
+        assert_eq!(
+            encode_int(i64::MIN),
+            vec![0xd3, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]
+        );
+    }
+
+    #[test]
+    fn test_serialize_negative_numbers() {
+        let number = |val: f64| serialize(JsonObject::Number(val));
+
+        assert_eq!(number(-1.0), vec![0xff]);
+        assert_eq!(number(-32.0), vec![0xe0]);
+        assert_eq!(number(-33.0), vec![0xd0, 0xdf]);
+        assert_eq!(number(-128.0), vec![0xd0, 0x80]);
+        assert_eq!(number(-129.0), vec![0xd1, 0xff, 0x7f]);
+        assert_eq!(
+            number(i64::MIN as f64),
+            vec![0xd3, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]
+        );
+        assert_eq!(
+            serialize(from_str("[-1, -200]").unwrap()),
+            vec![0x92, 0xff, 0xd1, 0xff, 0x38]
+        );
+    }
+
+    #[test]
+    fn test_serialize_wide_integers() {
+        assert_eq!(
+            try_serialize(&JsonObject::WideInteger(u64::MAX.into())),
+            Ok(vec![0xcf, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff])
+        );
+        assert_eq!(
+            try_serialize(&JsonObject::WideInteger(i128::from(u64::MAX) + 1)),
+            Err(SerializeError::IntegerTooWide(i128::from(u64::MAX) + 1))
+        );
+        assert_eq!(
+            try_serialize(&JsonObject::WideInteger(i128::from(i64::MIN) - 1)),
+            Err(SerializeError::IntegerTooWide(i128::from(i64::MIN) - 1))
+        );
+    }
+
+    #[test]
+    #[allow(clippy::approx_constant)]
+    fn test_serialize_floats() {
+        let number = |val: f64| serialize(JsonObject::Number(val));
+
+        assert_eq!(number(0.5), vec![0xca, 0x3f, 0x00, 0x00, 0x00]);
+        assert_eq!(number(-2.25), vec![0xca, 0xc0, 0x10, 0x00, 0x00]);
+        assert_eq!(
+            number(3.14),
+            vec![0xcb, 0x40, 0x09, 0x1e, 0xb8, 0x51, 0xeb, 0x85, 0x1f]
+        );
+        assert_eq!(
+            number(0.1),
+            vec![0xcb, 0x3f, 0xb9, 0x99, 0x99, 0x99, 0x99, 0x99, 0x9a]
+        );
+
+        // Integral values outside the 64-bit ranges are floats too.
+        assert_eq!(number(f64::INFINITY), vec![0xca, 0x7f, 0x80, 0x00, 0x00]);
+        assert_eq!(number(1e20)[0], 0xcb);
+        assert_eq!(number(f64::NAN)[0], 0xca);
+    }
+
+    #[test]
+    fn test_serialize_extreme_floats_round_trip() {
+        use crate::message_pack::deserialize::deserialize;
+
+        for val in [
+            f64::MAX,
+            f64::MIN,
+            f64::MIN_POSITIVE,
+            f64::from_bits(1),
+            -f64::from_bits(0x000f_ffff_ffff_ffff),
+            -0.1,
+            -1.0 / 3.0,
+            1e-40,
+            f64::EPSILON + 1.0,
+        ] {
+            let encoded = serialize(JsonObject::Number(val));
+            assert_eq!(encoded[0], 0xcb, "{:e}", val);
+
+            match deserialize(&encoded) {
+                Ok(JsonObject::Number(decoded)) => {
+                    assert_eq!(decoded.to_bits(), val.to_bits(), "{:e}", val)
+                }
+                other => panic!("unexpected result for {:e}: {:?}", val, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_serialize_negative_wide_integers() {
+        assert_eq!(try_serialize(&JsonObject::WideInteger(-5)), Ok(vec![0xfb]));
+        assert_eq!(
+            try_serialize(&JsonObject::WideInteger(i64::MIN.into())),
+            Ok(vec![0xd3, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00])
+        );
+        assert_eq!(
+            try_serialize(&JsonObject::WideInteger(-200)),
+            try_serialize(&JsonObject::Integer(-200))
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    #[should_panic(expected = "IntegerTooWide")]
+    fn test_serialize_panics_on_wide_integers() {
+        super::serialize(JsonObject::WideInteger(-(1 << 64)));
+    }
+
+    #[test]
+    fn test_serialize_integral_edges_round_trip() {
+        use crate::message_pack::deserialize::deserialize;
+
+        let json = from_str("18446744073709551616").unwrap();
+        assert_eq!(deserialize(&serialize(json.clone())), Ok(json));
+
+        let encoded = serialize(JsonObject::Number(-0.0));
+        assert_eq!(encoded, vec![0xca, 0x80, 0x00, 0x00, 0x00]);
+        match deserialize(&encoded) {
+            Ok(JsonObject::Number(decoded)) => {
+                assert!(decoded == 0.0 && decoded.is_sign_negative())
+            }
+            other => panic!("unexpected result for -0.0: {:?}", other),
+        }
+        assert_eq!(serialize(JsonObject::Number(0.0)), vec![0x00]);
+    }
+
+    #[test]
+    fn test_serialize_empty_values() {
+        assert_eq!(serialize(JsonObject::String(String::new())), vec![0xa0]);
+        assert_eq!(serialize(JsonObject::Array(vec![])), vec![0x90]);
+        assert_eq!(serialize(JsonObject::Object(HashMap::new())), vec![0x80]);
+    }
+
+    #[test]
+    fn test_serialize_string_lengths() {
+        let string = |len| serialize(JsonObject::String("a".repeat(len)));
+
+        assert_eq!(serialize(JsonObject::String(String::new())), vec![0xa0]);
+        assert_eq!(string(31)[..1], [0xbf]);
+        assert_eq!(string(31).len(), 1 + 31);
+        assert_eq!(string(32)[..2], [0xd9, 0x20]);
+        assert_eq!(string(255)[..2], [0xd9, 0xff]);
+        assert_eq!(string(256)[..3], [0xda, 0x01, 0x00]);
+        assert_eq!(string(65536)[..5], [0xdb, 0x00, 0x01, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn test_serialize_multibyte_string() {
+        // Three chars, but nine bytes of UTF-8.
+        let mut expected = vec![0xa9];
+        expected.extend("é中😀".as_bytes());
+        assert_eq!(serialize(JsonObject::String("é中😀".to_string())), expected);
+    }
+
+    #[test]
+    fn test_serialize_arrays() {
+        assert_eq!(serialize(JsonObject::Array(vec![])), vec![0x90]);
+        assert_eq!(
+            serialize(from_str("[1, 2, 300]").unwrap()),
+            vec![0x93, 0x01, 0x02, 0xcd, 0x01, 0x2c]
+        );
+        assert_eq!(
+            serialize(from_str("[[], [true, [null]]]").unwrap()),
+            vec![0x92, 0x90, 0x92, 0xc3, 0x91, 0xc0]
+        );
+    }
+
+    #[test]
+    fn test_serialize_array_length_boundaries() {
+        let array = |len| JsonObject::Array(vec![JsonObject::Null; len]);
+
+        assert_eq!(serialize(array(15))[..1], [0x9f]);
+        assert_eq!(serialize(array(16))[..3], [0xdc, 0x00, 0x10]);
+        assert_eq!(serialize(array(65535))[..3], [0xdc, 0xff, 0xff]);
+
+        let encoded = serialize(array(70_000));
+        assert_eq!(encoded[..5], [0xdd, 0x00, 0x01, 0x11, 0x70]);
+        assert_eq!(encoded.len(), 5 + 70_000);
+        assert!(encoded[5..].iter().all(|&byte| byte == 0xc0));
+    }
+
+    #[test]
+    fn test_serialize_maps() {
+        assert_eq!(
+            serialize(from_str(r#"{"k": true}"#).unwrap()),
+            vec![0x81, 0xa1, b'k', 0xc3]
+        );
+        assert_eq!(
+            serialize(from_str(r#"{"outer": {"": null}}"#).unwrap()),
+            [&[0x81, 0xa5][..], b"outer", &[0x81, 0xa0, 0xc0]].concat()
+        );
+
+        let map =
+            |len: usize| JsonObject::object_of((0..len).map(|i| (i.to_string(), JsonObject::Null)));
+        assert_eq!(serialize(map(15))[..1], [0x8f]);
+        assert_eq!(serialize(map(16))[..3], [0xde, 0x00, 0x10]);
+        assert_eq!(serialize(map(65536))[..5], [0xdf, 0x00, 0x01, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn test_serialize_sorted() {
+        let json = from_str(r#"{"b": 2, "c": {"z": 0, "y": 1}, "a": 1}"#).unwrap();
+        let expected = vec![
+            0x83, 0xa1, b'a', 0x01, 0xa1, b'b', 0x02, 0xa1, b'c', 0x82, 0xa1, b'y', 0x01, 0xa1,
+            b'z', 0x00,
+        ];
+        assert_eq!(serialize_sorted(&json), Ok(expected));
+    }
+
+    #[test]
+    fn test_serialize_integer_keys() {
+        let json = from_str(r#"{"7": true}"#).unwrap();
+        assert_eq!(serialize_integer_keys(&json), Ok(vec![0x81, 0x07, 0xc3]));
+        assert_eq!(try_serialize(&json), Ok(vec![0x81, 0xa1, b'7', 0xc3]));
+
+        let json = from_str(r#"{"a": {"-1": null}}"#).unwrap();
+        assert_eq!(
+            serialize_integer_keys(&json),
+            Ok(vec![0x81, 0xa1, b'a', 0x81, 0xff, 0xc0])
+        );
+
+        for key in ["07", "+7", "1.5", "99999999999999999999", ""] {
+            let json = JsonObject::object_of([(key.to_string(), JsonObject::Null)]);
+            assert_eq!(
+                serialize_integer_keys(&json),
+                try_serialize(&json),
+                "{}",
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn test_strings_always_use_str_by_default() {
+        for value in ["", "plain", "\u{0}\u{ff}\u{1f600}", &"\u{7f}".repeat(300)] {
+            let encoded = serialize(JsonObject::String(value.to_string()));
+            assert!(
+                matches!(encoded[0], 0xa0..=0xbf | 0xd9 | 0xda | 0xdb),
+                "{:#x}",
+                encoded[0]
+            );
+        }
+    }
+
+    #[test]
+    fn test_serialize_bin_strings() {
+        assert_eq!(
+            serialize_bin_strings(&JsonObject::String(String::new())),
+            Ok(vec![0xc4, 0x00])
+        );
+
+        let json = JsonObject::String("hi".to_string());
+        assert_eq!(
+            serialize_bin_strings(&json),
+            Ok(vec![0xc4, 0x02, b'h', b'i'])
+        );
+
+        let json = JsonObject::String("x".repeat(256));
+        assert_eq!(
+            serialize_bin_strings(&json).unwrap()[..3],
+            [0xc5, 0x01, 0x00]
+        );
+
+        let json = from_str(r#"{"k": ["v"]}"#).unwrap();
+        assert_eq!(
+            serialize_bin_strings(&json),
+            Ok(vec![0x81, 0xa1, b'k', 0x91, 0xc4, 0x01, b'v'])
+        );
+    }
+
+    #[test]
+    fn test_serialize_containers() {
+        let json = JsonObject::Object(HashMap::from([(
+            "a".to_string(),
+            JsonObject::Array(vec![JsonObject::Null, JsonObject::Number(1.0)]),
+        )]));
+
+        assert_eq!(serialize(json), vec![0x81, 0xa1, b'a', 0x92, 0xc0, 0x01]);
+    }
+
+    #[test]
+    fn test_serializer_reuses_buffer() {
+        let mut serializer = Serializer::new();
+
+        assert_eq!(
+            serializer.serialize_reuse(&JsonObject::String("hello".to_string())),
+            Ok(&[0xa5, b'h', b'e', b'l', b'l', b'o'][..])
+        );
+        assert_eq!(
+            serializer.serialize_reuse(&JsonObject::Null),
+            Ok(&[0xc0][..])
+        );
+
+        let json = JsonObject::Array(vec![JsonObject::Boolean(true), JsonObject::Number(300.0)]);
+        assert_eq!(
+            serializer.serialize_reuse(&json),
+            Ok(&serialize(json.clone())[..])
+        );
+
+        let capacity = serializer.buffer.capacity();
+        serializer
+            .serialize_reuse(&JsonObject::Boolean(false))
+            .unwrap();
+        assert_eq!(serializer.buffer.capacity(), capacity);
+    }
+
+    #[test]
+    fn test_try_into_bytes() {
+        let json = JsonObject::Object(HashMap::from([(
+            "ok".to_string(),
+            JsonObject::Boolean(true),
+        )]));
+
+        let bytes: Result<Vec<u8>, SerializeError> = json.try_into();
+        assert_eq!(bytes, Ok(vec![0x81, 0xa2, b'o', b'k', 0xc3]));
+        assert_eq!(check_len(u32::MAX as usize), Ok(()));
+        assert_eq!(
+            check_len(u32::MAX as usize + 1),
+            Err(SerializeError::TooLong(u32::MAX as usize + 1))
+        );
+    }
+
+    #[test]
+    fn test_serialize_checked_precision_loss() {
+        // Integer literals are kept exactly, so only the float path can lose
+        // precision.
+        let json = from_str("[9007199254740991, 9007199254740993]").unwrap();
+        assert_eq!(serialize_checked(&json), Ok(serialize(json)));
+
+        let json = from_str("[9007199254740991, 9007199254740993.0]").unwrap();
+        assert!(try_serialize(&json).is_ok());
+        assert_eq!(
+            serialize_checked(&json),
+            Err(SerializeError::PrecisionLoss(9007199254740992.0))
+        );
+
+        let json = from_str("[9007199254740991, 0.5e1]").unwrap();
+        assert_eq!(serialize_checked(&json), Ok(serialize(json)));
+        assert_eq!(
+            serialize_checked(&JsonObject::Number(-2e20)),
+            Err(SerializeError::PrecisionLoss(-2e20))
+        );
+    }
+
+    #[test]
+    fn test_serialize_exponent_integer() {
+        assert_eq!(serialize(from_str("5e3").unwrap()), vec![0xcd, 0x13, 0x88]);
+        assert_eq!(serialize(from_str("2.5E1").unwrap()), vec![0x19]);
     }
 }
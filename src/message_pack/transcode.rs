@@ -0,0 +1,404 @@
+use std::io::{self, BufReader, Bytes, Read, Seek, SeekFrom, Write};
+
+use super::serialize::{encode_int, serialize_number, serialize_str};
+use crate::parser::{
+    options::ParseOptions,
+    token::{ParseError, Token, Tokenizer},
+};
+
+#[derive(Debug)]
+pub enum TranscodeError {
+    Io(io::Error),
+    InvalidUtf8,
+    Parse(ParseError),
+    UnexpectedToken(Token),
+    /// An array or object has more than the `u32::MAX` elements MessagePack
+    /// can describe.
+    TooLong(usize),
+}
+
+impl From<io::Error> for TranscodeError {
+    fn from(error: io::Error) -> Self {
+        TranscodeError::Io(error)
+    }
+}
+
+impl From<ParseError> for TranscodeError {
+    fn from(error: ParseError) -> Self {
+        TranscodeError::Parse(error)
+    }
+}
+
+/// Reads a single JSON value from `reader` and writes it to `writer` as
+/// MessagePack, without building a `JsonObject` or a token list.
+///
+/// Values are written as soon as they are read, so memory use depends only
+/// on the nesting depth and the longest string, not on the document size.
+/// MessagePack container headers carry their element count, so arrays and
+/// objects are written with the 32-bit `array32`/`map32` headers and the
+/// count is patched in by seeking back when the container closes. The output
+/// is therefore larger than `try_serialize`'s for small containers, but
+/// decodes to the same values.
+///
+/// Duplicate object keys are copied to the output as they appear, while
+/// `from_str` keeps only the last one. Decoders that also keep the last
+/// entry, like `deserialize`, see the same object either way.
+pub fn transcode_reader_to_msgpack<R: Read, W: Write + Seek>(
+    reader: R,
+    mut writer: W,
+) -> Result<(), TranscodeError> {
+    let mut chars = ReadChars {
+        bytes: BufReader::new(reader).bytes(),
+        error: None,
+    };
+
+    let result = Output::new(&mut writer).and_then(|mut output| {
+        transcode(
+            Tokenizer::new(&mut chars, ParseOptions::default()),
+            &mut output,
+        )
+    });
+
+    // The tokenizer only sees the end of its input when reading fails, so the
+    // underlying error takes precedence over whatever it reported.
+    match chars.error.take() {
+        Some(error) => Err(error),
+        None => result,
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Expect {
+    Value,
+    ValueOrEnd,
+    Key,
+    KeyOrEnd,
+    Colon,
+    CommaOrEnd,
+}
+
+struct Frame {
+    is_object: bool,
+    expect: Expect,
+    len: usize,
+    /// Where the container's header starts in the output.
+    header: u64,
+}
+
+/// Tracks the write position so headers can be patched without asking the
+/// writer where it is after every value.
+struct Output<'a, W: Write + Seek> {
+    writer: &'a mut W,
+    position: u64,
+}
+
+impl<'a, W: Write + Seek> Output<'a, W> {
+    fn new(writer: &'a mut W) -> Result<Self, TranscodeError> {
+        let position = writer.stream_position()?;
+        Ok(Output { writer, position })
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), TranscodeError> {
+        self.writer.write_all(bytes)?;
+        self.position += bytes.len() as u64;
+        Ok(())
+    }
+
+    fn patch(&mut self, at: u64, bytes: &[u8]) -> Result<(), TranscodeError> {
+        self.writer.seek(SeekFrom::Start(at))?;
+        self.writer.write_all(bytes)?;
+        self.writer.seek(SeekFrom::Start(self.position))?;
+        Ok(())
+    }
+}
+
+fn transcode<I: Iterator<Item = char>, W: Write + Seek>(
+    tokens: Tokenizer<I>,
+    output: &mut Output<W>,
+) -> Result<(), TranscodeError> {
+    let max_depth = ParseOptions::default().max_depth;
+    let mut stack: Vec<Frame> = vec![];
+    let mut done = false;
+
+    for token in tokens {
+        let token = token?;
+        let expect = match stack.last() {
+            Some(frame) => frame.expect,
+            None if done => return Err(TranscodeError::UnexpectedToken(token)),
+            None => Expect::Value,
+        };
+        let in_object = stack.last().is_some_and(|frame| frame.is_object);
+
+        match (expect, token) {
+            (Expect::CommaOrEnd, Token::Comma) => {
+                let frame = stack.last_mut().unwrap();
+                frame.expect = if frame.is_object {
+                    Expect::Key
+                } else {
+                    Expect::Value
+                };
+            }
+            (Expect::Key | Expect::KeyOrEnd, Token::String(key)) => {
+                output.write(&serialize_str(&key))?;
+                stack.last_mut().unwrap().expect = Expect::Colon;
+            }
+            (Expect::Colon, Token::Colon) => stack.last_mut().unwrap().expect = Expect::Value,
+            (Expect::ValueOrEnd | Expect::CommaOrEnd, Token::CloseBracket) if !in_object => {
+                done = close(&mut stack, output)?;
+            }
+            (Expect::KeyOrEnd | Expect::CommaOrEnd, Token::CloseBrace) if in_object => {
+                done = close(&mut stack, output)?;
+            }
+            (Expect::Value | Expect::ValueOrEnd, Token::OpenBracket | Token::OpenBrace)
+                if stack.len() >= max_depth =>
+            {
+                return Err(ParseError::DepthLimitExceeded.into());
+            }
+            (Expect::Value | Expect::ValueOrEnd, Token::OpenBracket) => {
+                stack.push(open(output, false)?);
+            }
+            (Expect::Value | Expect::ValueOrEnd, Token::OpenBrace) => {
+                stack.push(open(output, true)?);
+            }
+            (Expect::Value | Expect::ValueOrEnd, Token::Null) => {
+                done = emit(&mut stack, output, &[0xc0])?;
+            }
+            (Expect::Value | Expect::ValueOrEnd, Token::Boolean(value)) => {
+                done = emit(&mut stack, output, &[if value { 0xc3 } else { 0xc2 }])?;
+            }
+            (Expect::Value | Expect::ValueOrEnd, Token::Integer(value)) => {
+                done = emit(&mut stack, output, &encode_int(value))?;
+            }
+            (Expect::Value | Expect::ValueOrEnd, Token::Number(value)) => {
+                done = emit(&mut stack, output, &serialize_number(value))?;
+            }
+            (Expect::Value | Expect::ValueOrEnd, Token::String(value)) => {
+                done = emit(&mut stack, output, &serialize_str(&value))?;
+            }
+            (_, token) => return Err(TranscodeError::UnexpectedToken(token)),
+        }
+    }
+
+    if done {
+        Ok(())
+    } else {
+        Err(ParseError::UnexpectedEndOfInput.into())
+    }
+}
+
+/// Writes a placeholder `array32` or `map32` header for a new container.
+fn open<W: Write + Seek>(output: &mut Output<W>, is_object: bool) -> Result<Frame, TranscodeError> {
+    let header = output.position;
+    output.write(&[if is_object { 0xdf } else { 0xdd }, 0, 0, 0, 0])?;
+    Ok(Frame {
+        is_object,
+        expect: if is_object {
+            Expect::KeyOrEnd
+        } else {
+            Expect::ValueOrEnd
+        },
+        len: 0,
+        header,
+    })
+}
+
+/// Patches the element count into the header of the innermost container.
+/// Returns whether the document is complete.
+fn close<W: Write + Seek>(
+    stack: &mut Vec<Frame>,
+    output: &mut Output<W>,
+) -> Result<bool, TranscodeError> {
+    let frame = stack.pop().unwrap();
+    let len = u32::try_from(frame.len).map_err(|_| TranscodeError::TooLong(frame.len))?;
+    output.patch(frame.header + 1, &len.to_be_bytes())?;
+    Ok(complete(stack))
+}
+
+/// Writes a scalar value. Returns whether the document is complete.
+fn emit<W: Write + Seek>(
+    stack: &mut [Frame],
+    output: &mut Output<W>,
+    encoded: &[u8],
+) -> Result<bool, TranscodeError> {
+    output.write(encoded)?;
+    Ok(complete(stack))
+}
+
+/// Counts a finished value in the innermost open container. Returns whether
+/// it was the top-level value.
+fn complete(stack: &mut [Frame]) -> bool {
+    match stack.last_mut() {
+        Some(frame) => {
+            frame.len += 1;
+            frame.expect = Expect::CommaOrEnd;
+            false
+        }
+        None => true,
+    }
+}
+
+/// Decodes UTF-8 characters from a byte stream. Reading stops at the first
+/// failure, which is kept in `error` for the caller to inspect.
+struct ReadChars<R: Read> {
+    bytes: Bytes<BufReader<R>>,
+    error: Option<TranscodeError>,
+}
+
+impl<R: Read> ReadChars<R> {
+    fn next_byte(&mut self) -> Option<u8> {
+        match self.bytes.next()? {
+            Ok(byte) => Some(byte),
+            Err(error) => {
+                self.error = Some(TranscodeError::Io(error));
+                None
+            }
+        }
+    }
+}
+
+impl<R: Read> Iterator for ReadChars<R> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        if self.error.is_some() {
+            return None;
+        }
+
+        let first = self.next_byte()?;
+        let width = match first {
+            0x00..=0x7f => return Some(first.into()),
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => 0,
+        };
+
+        let mut buffer = [first, 0, 0, 0];
+        for byte in buffer.iter_mut().take(width).skip(1) {
+            match self.next_byte() {
+                Some(next) => *byte = next,
+                None if self.error.is_some() => return None,
+                None => break,
+            }
+        }
+
+        match std::str::from_utf8(&buffer[..width]) {
+            Ok(char) if width > 0 => char.chars().next(),
+            _ => {
+                self.error = Some(TranscodeError::InvalidUtf8);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{
+        message_pack::{deserialize::deserialize, serialize::try_serialize},
+        parser::from_str,
+    };
+
+    fn transcode_str(input: &str) -> Result<Vec<u8>, TranscodeError> {
+        let mut output = Cursor::new(vec![]);
+        transcode_reader_to_msgpack(Cursor::new(input), &mut output)?;
+        Ok(output.into_inner())
+    }
+
+    #[test]
+    fn transcodes_large_array() {
+        let elements: Vec<String> = (0..100_000).map(|i| i.to_string()).collect();
+        let input = format!("[{}]", elements.join(","));
+
+        assert_eq!(
+            transcode_str(&input).unwrap(),
+            try_serialize(&from_str(&input).unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn transcodes_nested_documents() {
+        let input = r#"{"name": "chidjimi", "tags": ["a", {"b": [true, false, null]}], "n": 1000}"#;
+        let output = transcode_str(input).unwrap();
+
+        // Object key order is unspecified, so compare the decoded trees.
+        assert_eq!(deserialize(&output), Ok(from_str(input).unwrap()));
+        assert_eq!(transcode_str("\"é\"").unwrap(), vec![0xa2, 0xc3, 0xa9]);
+        assert_eq!(transcode_str(" 42 ").unwrap(), vec![42]);
+    }
+
+    #[test]
+    fn patches_container_headers() {
+        assert_eq!(
+            transcode_str(r#"[1, [], {"a": null}]"#).unwrap(),
+            vec![
+                0xdd, 0x00, 0x00, 0x00, 0x03, 0x01, 0xdd, 0x00, 0x00, 0x00, 0x00, 0xdf, 0x00, 0x00,
+                0x00, 0x01, 0xa1, b'a', 0xc0
+            ]
+        );
+
+        // Headers are patched relative to where the writer started.
+        let mut output = Cursor::new(vec![0xff, 0xff]);
+        output.set_position(2);
+        transcode_reader_to_msgpack(Cursor::new("[true]"), &mut output).unwrap();
+        assert_eq!(
+            output.into_inner(),
+            vec![0xff, 0xff, 0xdd, 0x00, 0x00, 0x00, 0x01, 0xc3]
+        );
+    }
+
+    #[test]
+    fn copies_duplicate_keys() {
+        let input = r#"{"a": 1, "a": 2}"#;
+        let output = transcode_str(input).unwrap();
+
+        assert_eq!(
+            output,
+            vec![0xdf, 0x00, 0x00, 0x00, 0x02, 0xa1, b'a', 0x01, 0xa1, b'a', 0x02]
+        );
+        assert_eq!(
+            try_serialize(&from_str(input).unwrap()),
+            Ok(vec![0x81, 0xa1, b'a', 0x02])
+        );
+        assert_eq!(deserialize(&output), Ok(from_str(input).unwrap()));
+    }
+
+    #[test]
+    fn transcode_errors() {
+        assert!(matches!(
+            transcode_str("[1 2]"),
+            Err(TranscodeError::UnexpectedToken(Token::Integer(_)))
+        ));
+        assert!(matches!(
+            transcode_str(r#"{"a" 1}"#),
+            Err(TranscodeError::UnexpectedToken(Token::Integer(_)))
+        ));
+        assert!(matches!(
+            transcode_str("[1,]"),
+            Err(TranscodeError::UnexpectedToken(Token::CloseBracket))
+        ));
+        assert!(matches!(
+            transcode_str("[1}"),
+            Err(TranscodeError::UnexpectedToken(Token::CloseBrace))
+        ));
+        assert!(matches!(
+            transcode_str("{} []"),
+            Err(TranscodeError::UnexpectedToken(Token::OpenBracket))
+        ));
+        assert!(matches!(
+            transcode_str("[1"),
+            Err(TranscodeError::Parse(ParseError::UnexpectedEndOfInput))
+        ));
+        assert!(matches!(
+            transcode_str(&"[".repeat(10_000)),
+            Err(TranscodeError::Parse(ParseError::DepthLimitExceeded))
+        ));
+
+        let mut output = Cursor::new(vec![]);
+        let result = transcode_reader_to_msgpack(Cursor::new(vec![b'"', 0xff, b'"']), &mut output);
+        assert!(matches!(result, Err(TranscodeError::InvalidUtf8)));
+    }
+}
@@ -0,0 +1,313 @@
+use std::{collections::HashMap, ops::Index};
+
+use crate::parser::JsonObject;
+
+static NULL: JsonObject = JsonObject::Null;
+
+/// Returned by `set_path`, carrying the pointer that could not be used.
+#[derive(Debug, PartialEq)]
+pub enum PatchError {
+    /// The pointer is not empty and does not start with `/`, or has a `~`
+    /// that is not part of `~0` or `~1`.
+    InvalidPointer(String),
+    /// No value exists at the pointer.
+    PathNotFound(String),
+}
+
+impl JsonObject {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonObject::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns any of the number variants as an `f64`, rounding integers
+    /// that have no exact `f64` representation.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            JsonObject::Number(value) => Some(*value),
+            JsonObject::Integer(value) => Some(*value as f64),
+            JsonObject::WideInteger(value) => Some(*value as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            JsonObject::Boolean(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<JsonObject>> {
+        match self {
+            JsonObject::Array(elements) => Some(elements),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&HashMap<String, JsonObject>> {
+        match self {
+            JsonObject::Object(elements) => Some(elements),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, JsonObject::Null)
+    }
+
+    /// Looks up `key` if this is an object.
+    pub fn get(&self, key: &str) -> Option<&JsonObject> {
+        match self {
+            JsonObject::Object(elements) => elements.get(key),
+            _ => None,
+        }
+    }
+
+    /// Looks up element `index` if this is an array.
+    pub fn get_index(&self, index: usize) -> Option<&JsonObject> {
+        match self {
+            JsonObject::Array(elements) => elements.get(index),
+            _ => None,
+        }
+    }
+
+    /// Returns the first of `values` that is not `Null`, or `Null` if there
+    /// is none, e.g. `coalesce(&[override, config, default])`.
+    pub fn coalesce<'a>(values: &[&'a JsonObject]) -> &'a JsonObject {
+        values
+            .iter()
+            .copied()
+            .find(|value| **value != JsonObject::Null)
+            .unwrap_or(&NULL)
+    }
+
+    /// Follows `path` through objects, and through arrays for segments that
+    /// are indices, returning the value at the end.
+    ///
+    /// With `create`, missing object keys are added and `Null` values along
+    /// the way become objects, so the path always resolves unless it runs
+    /// into an array or a scalar. A newly created leaf is `Null`.
+    pub fn get_path_mut(&mut self, path: &[&str], create: bool) -> Option<&mut JsonObject> {
+        let mut current = self;
+
+        for segment in path {
+            if create && *current == JsonObject::Null {
+                *current = JsonObject::Object(HashMap::new());
+            }
+
+            current = match current {
+                JsonObject::Object(elements) => {
+                    if create {
+                        elements
+                            .entry(segment.to_string())
+                            .or_insert(JsonObject::Null)
+                    } else {
+                        elements.get_mut(*segment)?
+                    }
+                }
+                JsonObject::Array(elements) => elements.get_mut(segment.parse::<usize>().ok()?)?,
+                _ => return None,
+            };
+        }
+
+        Some(current)
+    }
+
+    /// Replaces the value at the RFC 6901 JSON Pointer `pointer`, which must
+    /// already exist. The empty pointer replaces the whole document.
+    pub fn set_path(&mut self, pointer: &str, value: JsonObject) -> Result<(), PatchError> {
+        let segments = parse_pointer(pointer)
+            .ok_or_else(|| PatchError::InvalidPointer(pointer.to_string()))?;
+        let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+
+        let target = self
+            .get_path_mut(&segments, false)
+            .ok_or_else(|| PatchError::PathNotFound(pointer.to_string()))?;
+        *target = value;
+        Ok(())
+    }
+}
+
+/// Splits a JSON Pointer into its unescaped reference tokens.
+pub(crate) fn parse_pointer(pointer: &str) -> Option<Vec<String>> {
+    if pointer.is_empty() {
+        return Some(vec![]);
+    }
+
+    pointer
+        .strip_prefix('/')?
+        .split('/')
+        .map(|token| {
+            let mut segment = String::new();
+            let mut chars = token.chars();
+            while let Some(char) = chars.next() {
+                segment.push(match char {
+                    '~' => match chars.next()? {
+                        '0' => '~',
+                        '1' => '/',
+                        _ => return None,
+                    },
+                    char => char,
+                });
+            }
+            Some(segment)
+        })
+        .collect()
+}
+
+/// `json["key"]` is the value for `key`, or `Null` when `json` is not an
+/// object or has no such key. It never panics.
+impl Index<&str> for JsonObject {
+    type Output = JsonObject;
+
+    fn index(&self, key: &str) -> &JsonObject {
+        self.get(key).unwrap_or(&NULL)
+    }
+}
+
+/// `json[i]` is element `i`, or `Null` when `json` is not an array or is too
+/// short. It never panics.
+impl Index<usize> for JsonObject {
+    type Output = JsonObject;
+
+    fn index(&self, index: usize) -> &JsonObject {
+        self.get_index(index).unwrap_or(&NULL)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::from_str;
+
+    #[test]
+    fn typed_accessors_match_their_variant() {
+        let json =
+            from_str(r#"{"s": "x", "n": 1.5, "i": 2, "b": false, "a": [1], "z": null}"#).unwrap();
+
+        assert_eq!(json["s"].as_str(), Some("x"));
+        assert_eq!(json["n"].as_str(), None);
+        assert_eq!(json["n"].as_f64(), Some(1.5));
+        assert_eq!(json["i"].as_f64(), Some(2.0));
+        assert_eq!(
+            JsonObject::WideInteger(1 << 70).as_f64(),
+            Some(2f64.powi(70))
+        );
+        assert_eq!(json["s"].as_f64(), None);
+        assert_eq!(json["b"].as_bool(), Some(false));
+        assert_eq!(json["z"].as_bool(), None);
+        assert_eq!(json["a"].as_array(), Some(&vec![JsonObject::Integer(1)]));
+        assert_eq!(json.as_array(), None);
+        assert_eq!(json.as_object().map(HashMap::len), Some(6));
+        assert_eq!(json["a"].as_object(), None);
+        assert!(json["z"].is_null());
+        assert!(json["missing"].is_null());
+        assert!(!json["b"].is_null());
+    }
+
+    #[test]
+    fn get_path_mut_creates_intermediate_objects() {
+        let mut json = JsonObject::Object(HashMap::new());
+
+        *json.get_path_mut(&["a", "b", "c"], true).unwrap() = JsonObject::Number(1.0);
+        *json.get_path_mut(&["a", "d"], true).unwrap() = JsonObject::Boolean(true);
+
+        assert_eq!(
+            json,
+            from_str(r#"{"a": {"b": {"c": 1}, "d": true}}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn get_path_mut_without_create() {
+        let mut json = from_str(r#"{"a": [{"b": 1}, 2], "c": null}"#).unwrap();
+
+        *json.get_path_mut(&["a", "0", "b"], false).unwrap() = JsonObject::Null;
+        assert_eq!(
+            json,
+            from_str(r#"{"a": [{"b": null}, 2], "c": null}"#).unwrap()
+        );
+
+        assert_eq!(json.get_path_mut(&["missing"], false), None);
+        assert_eq!(json.get_path_mut(&["a", "5"], false), None);
+        assert_eq!(json.get_path_mut(&["a", "x"], true), None);
+        assert_eq!(json.get_path_mut(&["a", "1", "x"], true), None);
+        let mut root = json.clone();
+        assert_eq!(json.get_path_mut(&[], false), Some(&mut root));
+
+        assert!(json.get_path_mut(&["c", "d"], true).is_some());
+        assert_eq!(
+            json,
+            from_str(r#"{"a": [{"b": null}, 2], "c": {"d": null}}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn set_path_replaces_existing_values() {
+        let mut json = from_str(r#"{"a": [{"b": 1}, 2], "c/d": {"~e": 3}}"#).unwrap();
+
+        json.set_path("/a/0/b", JsonObject::Boolean(true)).unwrap();
+        json.set_path("/c~1d/~0e", JsonObject::Null).unwrap();
+        json.set_path("/a/1", JsonObject::array_of([])).unwrap();
+        assert_eq!(
+            json,
+            from_str(r#"{"a": [{"b": true}, []], "c/d": {"~e": null}}"#).unwrap()
+        );
+
+        json.set_path("", JsonObject::Integer(1)).unwrap();
+        assert_eq!(json, JsonObject::Integer(1));
+    }
+
+    #[test]
+    fn set_path_requires_an_existing_location() {
+        let mut json = from_str(r#"{"a": [1], "b": null}"#).unwrap();
+        let original = json.clone();
+
+        for pointer in ["/missing", "/a/1", "/a/x", "/b/c", "/a/0/d"] {
+            assert_eq!(
+                json.set_path(pointer, JsonObject::Null),
+                Err(PatchError::PathNotFound(pointer.to_string()))
+            );
+        }
+        for pointer in ["a", "/a~2", "/a~"] {
+            assert_eq!(
+                json.set_path(pointer, JsonObject::Null),
+                Err(PatchError::InvalidPointer(pointer.to_string()))
+            );
+        }
+        assert_eq!(json, original);
+    }
+
+    #[test]
+    fn coalesce_skips_nulls() {
+        let unset = JsonObject::Null;
+        let config = from_str(r#"{"port": 80}"#).unwrap();
+        let default = JsonObject::Number(8080.0);
+
+        assert_eq!(JsonObject::coalesce(&[&unset, &config, &default]), &config);
+        assert_eq!(JsonObject::coalesce(&[&unset, &default]), &default);
+        assert_eq!(JsonObject::coalesce(&[&unset, &unset]), &JsonObject::Null);
+        assert_eq!(JsonObject::coalesce(&[]), &JsonObject::Null);
+    }
+
+    #[test]
+    fn indexes_objects_and_arrays() {
+        let json = from_str(r#"{"a": [10, {"b": "x"}], "n": null}"#).unwrap();
+
+        assert_eq!(json["a"][0], JsonObject::Integer(10));
+        assert_eq!(json["a"][1]["b"], JsonObject::String("x".to_string()));
+        assert_eq!(json["missing"], JsonObject::Null);
+        assert_eq!(json["a"][5], JsonObject::Null);
+        assert_eq!(json["a"]["b"], JsonObject::Null);
+        assert_eq!(json[0], JsonObject::Null);
+
+        assert_eq!(json.get("n"), Some(&JsonObject::Null));
+        assert_eq!(json.get("missing"), None);
+        assert_eq!(json["a"].get_index(1), Some(&json["a"][1]));
+        assert_eq!(json["a"].get_index(2), None);
+        assert_eq!(json.get_index(0), None);
+    }
+}
@@ -0,0 +1,182 @@
+use std::collections::{hash_map::Entry, HashMap};
+
+use crate::parser::JsonObject;
+
+/// Returned by `insert_unique` along with the value that was not inserted.
+#[derive(Debug, PartialEq)]
+pub struct DuplicateKeyError {
+    pub key: String,
+    pub value: JsonObject,
+}
+
+impl JsonObject {
+    pub fn array_of<I: IntoIterator<Item = JsonObject>>(elements: I) -> Self {
+        JsonObject::Array(elements.into_iter().collect())
+    }
+
+    /// An empty array with room for `capacity` elements.
+    pub fn array_with_capacity(capacity: usize) -> Self {
+        JsonObject::Array(Vec::with_capacity(capacity))
+    }
+
+    /// An empty object with room for `capacity` entries.
+    pub fn object_with_capacity(capacity: usize) -> Self {
+        JsonObject::Object(HashMap::with_capacity(capacity))
+    }
+
+    /// Builds an object from key/value pairs. Later duplicates of a key
+    /// replace earlier ones.
+    pub fn object_of<I: IntoIterator<Item = (String, JsonObject)>>(entries: I) -> Self {
+        JsonObject::Object(entries.into_iter().collect())
+    }
+
+    /// Turns `Null` into an empty array and returns the array.
+    ///
+    /// Panics if the value is neither `Null` nor an array.
+    pub fn ensure_array(&mut self) -> &mut Vec<JsonObject> {
+        if let JsonObject::Null = self {
+            *self = JsonObject::Array(vec![]);
+        }
+
+        match self {
+            JsonObject::Array(elements) => elements,
+            _ => panic!("Expected array or null"),
+        }
+    }
+
+    /// Turns `Null` into an empty object and returns the object.
+    ///
+    /// Panics if the value is neither `Null` nor an object.
+    pub fn ensure_object(&mut self) -> &mut HashMap<String, JsonObject> {
+        if let JsonObject::Null = self {
+            *self = JsonObject::Object(HashMap::new());
+        }
+
+        match self {
+            JsonObject::Object(elements) => elements,
+            _ => panic!("Expected object or null"),
+        }
+    }
+
+    /// Inserts a new entry, turning `Null` into an object first. Unlike
+    /// `HashMap::insert`, an existing key is left untouched and reported.
+    ///
+    /// Panics if the value is neither `Null` nor an object.
+    pub fn insert_unique(
+        &mut self,
+        key: String,
+        value: JsonObject,
+    ) -> Result<(), DuplicateKeyError> {
+        match self.ensure_object().entry(key) {
+            Entry::Occupied(entry) => Err(DuplicateKeyError {
+                key: entry.key().to_string(),
+                value,
+            }),
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_array_from_iterator() {
+        let json = JsonObject::array_of((1..=3).map(|i| JsonObject::Number(i as f64)));
+
+        assert_eq!(
+            json,
+            JsonObject::Array(vec![
+                JsonObject::Number(1.0),
+                JsonObject::Number(2.0),
+                JsonObject::Number(3.0),
+            ])
+        );
+    }
+
+    #[test]
+    fn builds_object_from_iterator() {
+        let json = JsonObject::object_of(
+            ["a", "b"]
+                .iter()
+                .map(|key| (key.to_string(), JsonObject::String(key.to_uppercase()))),
+        );
+
+        let mut map = HashMap::new();
+        map.insert("a".to_string(), JsonObject::String("A".to_string()));
+        map.insert("b".to_string(), JsonObject::String("B".to_string()));
+        assert_eq!(json, JsonObject::Object(map));
+    }
+
+    #[test]
+    fn ensure_array_coerces_null() {
+        let mut json = JsonObject::Null;
+        json.ensure_array().push(JsonObject::Number(1.0));
+        json.ensure_array().push(JsonObject::Number(2.0));
+
+        assert_eq!(
+            json,
+            JsonObject::Array(vec![JsonObject::Number(1.0), JsonObject::Number(2.0)])
+        );
+    }
+
+    #[test]
+    fn ensure_object_coerces_null() {
+        let mut json = JsonObject::Null;
+        json.ensure_object()
+            .insert("foo".to_string(), JsonObject::Boolean(true));
+
+        let mut map = HashMap::new();
+        map.insert("foo".to_string(), JsonObject::Boolean(true));
+        assert_eq!(json, JsonObject::Object(map));
+    }
+
+    #[test]
+    fn insert_unique_rejects_duplicates() {
+        let mut json = JsonObject::Null;
+        assert_eq!(
+            json.insert_unique("a".to_string(), JsonObject::Number(1.0)),
+            Ok(())
+        );
+        assert_eq!(
+            json.insert_unique("a".to_string(), JsonObject::Number(2.0)),
+            Err(DuplicateKeyError {
+                key: "a".to_string(),
+                value: JsonObject::Number(2.0),
+            })
+        );
+
+        let mut map = HashMap::new();
+        map.insert("a".to_string(), JsonObject::Number(1.0));
+        assert_eq!(json, JsonObject::Object(map));
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected array or null")]
+    fn ensure_array_panics_on_conflict() {
+        JsonObject::Boolean(true).ensure_array();
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected object or null")]
+    fn ensure_object_panics_on_conflict() {
+        JsonObject::Array(vec![]).ensure_object();
+    }
+
+    #[test]
+    fn reserves_capacity() {
+        let mut json = JsonObject::array_with_capacity(100);
+        let elements = json.ensure_array();
+        assert!(elements.is_empty());
+        assert!(elements.capacity() >= 100);
+
+        let mut json = JsonObject::object_with_capacity(100);
+        let entries = json.ensure_object();
+        assert!(entries.is_empty());
+        assert!(entries.capacity() >= 100);
+    }
+}
@@ -0,0 +1,67 @@
+use crate::parser::JsonObject;
+
+impl JsonObject {
+    /// Checks that every entry of `self` appears in `other`, recursively.
+    /// Objects in `other` may have extra keys, while arrays must have the
+    /// same length with each element a subset of its counterpart. Any other
+    /// values must be equal.
+    pub fn is_subset_of(&self, other: &JsonObject) -> bool {
+        match (self, other) {
+            (JsonObject::Object(expected), JsonObject::Object(actual)) => {
+                expected.iter().all(|(key, value)| {
+                    actual
+                        .get(key)
+                        .is_some_and(|actual| value.is_subset_of(actual))
+                })
+            }
+            (JsonObject::Array(expected), JsonObject::Array(actual)) => {
+                expected.len() == actual.len()
+                    && expected
+                        .iter()
+                        .zip(actual)
+                        .all(|(expected, actual)| expected.is_subset_of(actual))
+            }
+            (expected, actual) => expected == actual,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::from_str;
+
+    #[test]
+    fn matches_partial_responses() {
+        let response = from_str(
+            r#"{"id": 7, "user": {"name": "a", "roles": ["admin"], "meta": {}},
+                "items": [{"sku": "x", "qty": 1}, {"sku": "y", "qty": 2}]}"#,
+        )
+        .unwrap();
+        let expected =
+            from_str(r#"{"user": {"name": "a"}, "items": [{"sku": "x"}, {"qty": 2}]}"#).unwrap();
+
+        assert!(expected.is_subset_of(&response));
+        assert!(response.is_subset_of(&response));
+        assert!(!response.is_subset_of(&expected));
+    }
+
+    #[test]
+    fn rejects_mismatches() {
+        let actual = from_str(r#"{"a": [1, 2], "b": null}"#).unwrap();
+
+        for expected in [
+            r#"{"a": [1]}"#,
+            r#"{"a": [1, 3]}"#,
+            r#"{"b": false}"#,
+            r#"{"c": null}"#,
+            "[]",
+        ] {
+            assert!(
+                !from_str(expected).unwrap().is_subset_of(&actual),
+                "{}",
+                expected
+            );
+        }
+        assert!(from_str("{}").unwrap().is_subset_of(&actual));
+    }
+}
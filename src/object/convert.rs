@@ -0,0 +1,372 @@
+use std::collections::HashMap;
+
+use super::access::parse_pointer;
+use crate::{
+    json::serialize::to_string,
+    parser::{from_str, token::ParseError, JsonObject},
+};
+
+#[derive(Debug, PartialEq)]
+pub enum ConversionError {
+    UnexpectedType {
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// The element at `index` of an array could not be converted.
+    AtIndex {
+        index: usize,
+        error: Box<ConversionError>,
+    },
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseIntoError {
+    Parse(ParseError),
+    Conversion(ConversionError),
+}
+
+/// Returned by `try_get_path`. The pointer is included when the path could
+/// not be followed.
+#[derive(Debug, PartialEq)]
+pub enum PathError {
+    InvalidPointer(String),
+    Missing(String),
+    /// The value exists but could not be converted.
+    Conversion(ConversionError),
+}
+
+impl From<ParseError> for ParseIntoError {
+    fn from(error: ParseError) -> Self {
+        ParseIntoError::Parse(error)
+    }
+}
+
+impl From<ConversionError> for ParseIntoError {
+    fn from(error: ConversionError) -> Self {
+        ParseIntoError::Conversion(error)
+    }
+}
+
+pub trait FromJson: Sized {
+    fn from_json(object: &JsonObject) -> Result<Self, ConversionError>;
+}
+
+/// Parses JSON text and converts the resulting document into `T`.
+pub fn parse_into<T: FromJson>(input: &str) -> Result<T, ParseIntoError> {
+    let object = from_str(input)?;
+    Ok(T::from_json(&object)?)
+}
+
+impl JsonObject {
+    /// Converts every element of an array into `T`, reporting the index of
+    /// the first element that fails.
+    pub fn as_array_of<T: FromJson>(&self) -> Result<Vec<T>, ConversionError> {
+        match self {
+            JsonObject::Array(elements) => elements
+                .iter()
+                .enumerate()
+                .map(|(index, element)| {
+                    T::from_json(element).map_err(|error| ConversionError::AtIndex {
+                        index,
+                        error: Box::new(error),
+                    })
+                })
+                .collect(),
+            _ => Err(unexpected_type("array", self)),
+        }
+    }
+
+    /// Converts the value at the RFC 6901 JSON Pointer `pointer` into `T`.
+    pub fn try_get_path<T: FromJson>(&self, pointer: &str) -> Result<T, PathError> {
+        let missing = || PathError::Missing(pointer.to_string());
+        let segments =
+            parse_pointer(pointer).ok_or_else(|| PathError::InvalidPointer(pointer.to_string()))?;
+
+        let mut current = self;
+        for segment in &segments {
+            current = current
+                .get(segment)
+                .or_else(|| current.get_index(segment.parse().ok()?))
+                .ok_or_else(missing)?;
+        }
+
+        T::from_json(current).map_err(PathError::Conversion)
+    }
+
+    /// Flattens the document into `(key, value)` pairs, one per leaf, for
+    /// tabular export. Keys join the path segments with `separator`, using
+    /// indices for array elements; the root itself has the empty key.
+    ///
+    /// Strings are rendered as they are, other leaves (including empty
+    /// arrays and objects) as JSON text. Object keys are visited in sorted
+    /// order.
+    pub fn to_flat_pairs(&self, separator: char) -> Vec<(String, String)> {
+        let mut pairs = vec![];
+        flatten(self, &mut String::new(), separator, &mut pairs);
+        pairs
+    }
+
+    /// Consumes an object into its entries, sorted by key. Other values
+    /// yield no pairs.
+    pub fn into_sorted_pairs(self) -> Vec<(String, JsonObject)> {
+        match self {
+            JsonObject::Object(elements) => {
+                let mut pairs: Vec<_> = elements.into_iter().collect();
+                pairs.sort_by(|(a, _), (b, _)| a.cmp(b));
+                pairs
+            }
+            _ => vec![],
+        }
+    }
+}
+
+fn flatten(
+    object: &JsonObject,
+    prefix: &mut String,
+    separator: char,
+    pairs: &mut Vec<(String, String)>,
+) {
+    let mut visit = |segment: &str, value: &JsonObject| {
+        let len = prefix.len();
+        if !prefix.is_empty() {
+            prefix.push(separator);
+        }
+        prefix.push_str(segment);
+        flatten(value, prefix, separator, pairs);
+        prefix.truncate(len);
+    };
+
+    match object {
+        JsonObject::Object(elements) if !elements.is_empty() => {
+            let mut entries: Vec<_> = elements.iter().collect();
+            entries.sort_by_key(|(key, _)| *key);
+            for (key, value) in entries {
+                visit(key, value);
+            }
+        }
+        JsonObject::Array(elements) if !elements.is_empty() => {
+            for (i, element) in elements.iter().enumerate() {
+                visit(&i.to_string(), element);
+            }
+        }
+        JsonObject::String(value) => pairs.push((prefix.to_string(), value.to_string())),
+        _ => pairs.push((prefix.to_string(), to_string(object))),
+    }
+}
+
+fn type_name(object: &JsonObject) -> &'static str {
+    match object {
+        JsonObject::Object(_) => "object",
+        JsonObject::Array(_) => "array",
+        JsonObject::String(_) => "string",
+        JsonObject::Integer(_) | JsonObject::Number(_) | JsonObject::WideInteger(_) => "number",
+        JsonObject::Boolean(_) => "boolean",
+        JsonObject::Null => "null",
+    }
+}
+
+fn unexpected_type(expected: &'static str, found: &JsonObject) -> ConversionError {
+    ConversionError::UnexpectedType {
+        expected,
+        found: type_name(found),
+    }
+}
+
+impl FromJson for f64 {
+    fn from_json(object: &JsonObject) -> Result<Self, ConversionError> {
+        match object {
+            JsonObject::Integer(value) => Ok(*value as f64),
+            JsonObject::Number(value) => Ok(*value),
+            JsonObject::WideInteger(value) => Ok(*value as f64),
+            _ => Err(unexpected_type("number", object)),
+        }
+    }
+}
+
+impl FromJson for bool {
+    fn from_json(object: &JsonObject) -> Result<Self, ConversionError> {
+        match object {
+            JsonObject::Boolean(value) => Ok(*value),
+            _ => Err(unexpected_type("boolean", object)),
+        }
+    }
+}
+
+impl FromJson for String {
+    fn from_json(object: &JsonObject) -> Result<Self, ConversionError> {
+        match object {
+            JsonObject::String(value) => Ok(value.to_string()),
+            _ => Err(unexpected_type("string", object)),
+        }
+    }
+}
+
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(object: &JsonObject) -> Result<Self, ConversionError> {
+        match object {
+            JsonObject::Null => Ok(None),
+            _ => T::from_json(object).map(Some),
+        }
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(object: &JsonObject) -> Result<Self, ConversionError> {
+        match object {
+            JsonObject::Array(elements) => elements.iter().map(T::from_json).collect(),
+            _ => Err(unexpected_type("array", object)),
+        }
+    }
+}
+
+impl<T: FromJson> FromJson for HashMap<String, T> {
+    fn from_json(object: &JsonObject) -> Result<Self, ConversionError> {
+        match object {
+            JsonObject::Object(elements) => elements
+                .iter()
+                .map(|(key, value)| Ok((key.to_string(), T::from_json(value)?)))
+                .collect(),
+            _ => Err(unexpected_type("object", object)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_get_path_converts_nested_values() {
+        let json = from_str(r#"{"a": {"b": [{"c": 4}, "x"]}, "d/e": true}"#).unwrap();
+
+        assert_eq!(json.try_get_path::<f64>("/a/b/0/c"), Ok(4.0));
+        assert_eq!(json.try_get_path::<bool>("/d~1e"), Ok(true));
+        assert_eq!(json.try_get_path::<String>("/a/b/1"), Ok("x".to_string()));
+    }
+
+    #[test]
+    fn try_get_path_errors() {
+        let json = from_str(r#"{"a": {"b": [1]}}"#).unwrap();
+
+        for pointer in ["/missing", "/a/b/1", "/a/b/x", "/a/b/0/c"] {
+            assert_eq!(
+                json.try_get_path::<f64>(pointer),
+                Err(PathError::Missing(pointer.to_string()))
+            );
+        }
+        assert_eq!(
+            json.try_get_path::<f64>("a/b"),
+            Err(PathError::InvalidPointer("a/b".to_string()))
+        );
+        assert_eq!(
+            json.try_get_path::<String>("/a/b/0"),
+            Err(PathError::Conversion(ConversionError::UnexpectedType {
+                expected: "string",
+                found: "number",
+            }))
+        );
+    }
+
+    #[test]
+    fn parse_into_vec() {
+        let numbers: Vec<f64> = parse_into("[1,2,3]").unwrap();
+        assert_eq!(numbers, vec![1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn parse_into_nested_types() {
+        let map: HashMap<String, Option<bool>> = parse_into(r#"{"a": true, "b": null}"#).unwrap();
+        assert_eq!(map.get("a"), Some(&Some(true)));
+        assert_eq!(map.get("b"), Some(&None));
+    }
+
+    #[test]
+    fn parse_into_errors() {
+        let result: Result<Vec<f64>, _> = parse_into(r#"[1, "two"]"#);
+        assert_eq!(
+            result,
+            Err(ParseIntoError::Conversion(
+                ConversionError::UnexpectedType {
+                    expected: "number",
+                    found: "string",
+                }
+            ))
+        );
+
+        let result: Result<Vec<f64>, _> = parse_into("[a]");
+        assert_eq!(result, Err(ParseIntoError::Parse(ParseError::InvalidToken)));
+    }
+
+    #[test]
+    fn flattens_to_pairs() {
+        let json = from_str(
+            r#"{"name": "ada", "tags": ["x", "y"], "address": {"zip": 12345, "geo": null}, "extra": {}}"#,
+        )
+        .unwrap();
+
+        let pair = |key: &str, value: &str| (key.to_string(), value.to_string());
+        assert_eq!(
+            json.to_flat_pairs('.'),
+            vec![
+                pair("address.geo", "null"),
+                pair("address.zip", "12345"),
+                pair("extra", "{}"),
+                pair("name", "ada"),
+                pair("tags.0", "x"),
+                pair("tags.1", "y"),
+            ]
+        );
+        assert_eq!(
+            from_str(r#"[[true]]"#).unwrap().to_flat_pairs('/'),
+            vec![pair("0/0", "true")]
+        );
+        assert_eq!(JsonObject::Null.to_flat_pairs('.'), vec![pair("", "null")]);
+    }
+
+    #[test]
+    fn into_sorted_pairs_orders_keys() {
+        let json = from_str(r#"{"b": 2, "c": [3], "a": 1}"#).unwrap();
+
+        assert_eq!(
+            json.into_sorted_pairs(),
+            vec![
+                ("a".to_string(), JsonObject::Number(1.0)),
+                ("b".to_string(), JsonObject::Number(2.0)),
+                (
+                    "c".to_string(),
+                    JsonObject::Array(vec![JsonObject::Number(3.0)])
+                ),
+            ]
+        );
+        assert_eq!(JsonObject::Null.into_sorted_pairs(), vec![]);
+    }
+
+    #[test]
+    fn as_array_of_reports_index() {
+        let json = from_str(r#"["a", "b"]"#).unwrap();
+        assert_eq!(
+            json.as_array_of::<String>(),
+            Ok(vec!["a".to_string(), "b".to_string()])
+        );
+
+        let json = from_str(r#"["a", "b", 3, "d"]"#).unwrap();
+        assert_eq!(
+            json.as_array_of::<String>(),
+            Err(ConversionError::AtIndex {
+                index: 2,
+                error: Box::new(ConversionError::UnexpectedType {
+                    expected: "string",
+                    found: "number",
+                }),
+            })
+        );
+
+        assert_eq!(
+            JsonObject::Null.as_array_of::<String>(),
+            Err(ConversionError::UnexpectedType {
+                expected: "array",
+                found: "null",
+            })
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+
+use crate::parser::JsonObject;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ArrayMerge {
+    /// The incoming array replaces the existing one.
+    #[default]
+    Replace,
+    /// The incoming elements are appended to the existing ones.
+    Concat,
+    /// Incoming elements are appended unless an equal element is already present.
+    Union,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MergeOptions {
+    pub arrays: ArrayMerge,
+}
+
+impl JsonObject {
+    /// Deep-merges `other` into `self` using the default options.
+    pub fn merge(&mut self, other: JsonObject) {
+        self.merge_with(other, MergeOptions::default());
+    }
+
+    /// Deep-merges `docs` from left to right with the default options, so
+    /// later documents take precedence. An empty list gives `Null`.
+    pub fn merge_all(docs: Vec<JsonObject>) -> JsonObject {
+        let mut docs = docs.into_iter();
+        let mut merged = docs.next().unwrap_or(JsonObject::Null);
+        for doc in docs {
+            merged.merge(doc);
+        }
+        merged
+    }
+
+    /// Deep-merges `other` into `self`. Objects are merged key by key, arrays
+    /// follow `options.arrays`, and any other value from `other` replaces the
+    /// existing one.
+    pub fn merge_with(&mut self, other: JsonObject, options: MergeOptions) {
+        match (self, other) {
+            (JsonObject::Object(target), JsonObject::Object(source)) => {
+                for (key, value) in source {
+                    match target.get_mut(&key) {
+                        Some(existing) => existing.merge_with(value, options),
+                        None => {
+                            target.insert(key, value);
+                        }
+                    }
+                }
+            }
+            (JsonObject::Array(target), JsonObject::Array(source))
+                if options.arrays != ArrayMerge::Replace =>
+            {
+                for value in source {
+                    if options.arrays == ArrayMerge::Concat || !target.contains(&value) {
+                        target.push(value);
+                    }
+                }
+            }
+            (target, other) => *target = other,
+        }
+    }
+
+    /// Applies `patch` to `self` as an RFC 7396 JSON Merge Patch. Object keys
+    /// are merged recursively, `null` values in the patch remove keys, and any
+    /// other patch replaces `self` wholesale.
+    pub fn merge_patch(&mut self, patch: &JsonObject) {
+        let JsonObject::Object(patch) = patch else {
+            *self = patch.clone();
+            return;
+        };
+
+        if !matches!(self, JsonObject::Object(_)) {
+            *self = JsonObject::Object(HashMap::new());
+        }
+        let JsonObject::Object(target) = self else {
+            unreachable!()
+        };
+
+        for (key, value) in patch {
+            if *value == JsonObject::Null {
+                target.remove(key);
+            } else {
+                target
+                    .entry(key.clone())
+                    .or_insert(JsonObject::Null)
+                    .merge_patch(value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::from_str;
+
+    fn numbers(values: &[f64]) -> JsonObject {
+        JsonObject::Array(
+            values
+                .iter()
+                .map(|&value| JsonObject::Number(value))
+                .collect(),
+        )
+    }
+
+    fn merge_arrays(arrays: ArrayMerge) -> JsonObject {
+        let mut json = numbers(&[1.0, 2.0]);
+        json.merge_with(numbers(&[2.0, 3.0]), MergeOptions { arrays });
+        json
+    }
+
+    #[test]
+    fn merges_objects_recursively() {
+        let mut inner = HashMap::new();
+        inner.insert("a".to_string(), JsonObject::Number(1.0));
+        inner.insert("b".to_string(), JsonObject::Number(2.0));
+        let mut map = HashMap::new();
+        map.insert("inner".to_string(), JsonObject::Object(inner));
+        map.insert("keep".to_string(), JsonObject::Null);
+        let mut json = JsonObject::Object(map);
+
+        let mut inner = HashMap::new();
+        inner.insert("b".to_string(), JsonObject::Boolean(true));
+        let mut map = HashMap::new();
+        map.insert("inner".to_string(), JsonObject::Object(inner));
+        json.merge(JsonObject::Object(map));
+
+        let mut inner = HashMap::new();
+        inner.insert("a".to_string(), JsonObject::Number(1.0));
+        inner.insert("b".to_string(), JsonObject::Boolean(true));
+        let mut map = HashMap::new();
+        map.insert("inner".to_string(), JsonObject::Object(inner));
+        map.insert("keep".to_string(), JsonObject::Null);
+        assert_eq!(json, JsonObject::Object(map));
+    }
+
+    #[test]
+    fn merges_arrays_by_policy() {
+        assert_eq!(merge_arrays(ArrayMerge::Replace), numbers(&[2.0, 3.0]));
+        assert_eq!(
+            merge_arrays(ArrayMerge::Concat),
+            numbers(&[1.0, 2.0, 2.0, 3.0])
+        );
+        assert_eq!(merge_arrays(ArrayMerge::Union), numbers(&[1.0, 2.0, 3.0]));
+    }
+
+    #[test]
+    fn merge_replaces_mismatched_types() {
+        let mut json = numbers(&[1.0]);
+        json.merge(JsonObject::String("foo".to_string()));
+        assert_eq!(json, JsonObject::String("foo".to_string()));
+    }
+
+    #[test]
+    fn merge_patch_removes_null_keys() {
+        let mut json = from_str(r#"{"a": 1, "b": 2, "c": null}"#).unwrap();
+        json.merge_patch(&from_str(r#"{"b": null, "d": null, "e": 3}"#).unwrap());
+        assert_eq!(json, from_str(r#"{"a": 1, "c": null, "e": 3}"#).unwrap());
+    }
+
+    #[test]
+    fn merge_patch_merges_nested_objects() {
+        let mut json =
+            from_str(r#"{"title": "x", "author": {"name": "a", "email": "e"}, "tags": [1, 2]}"#)
+                .unwrap();
+        let patch = r#"{"author": {"email": null, "url": {"home": "h"}}, "tags": [3]}"#;
+        json.merge_patch(&from_str(patch).unwrap());
+        assert_eq!(
+            json,
+            from_str(
+                r#"{"title": "x", "author": {"name": "a", "url": {"home": "h"}}, "tags": [3]}"#
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn merge_patch_replaces_non_objects() {
+        let mut json = from_str(r#"{"a": 1}"#).unwrap();
+        json.merge_patch(&from_str("[1]").unwrap());
+        assert_eq!(json, numbers(&[1.0]));
+
+        let mut json = from_str(r#""text""#).unwrap();
+        json.merge_patch(&from_str(r#"{"a": {"b": null, "c": true}}"#).unwrap());
+        assert_eq!(json, from_str(r#"{"a": {"c": true}}"#).unwrap());
+    }
+
+    #[test]
+    fn merge_all_applies_layers_in_order() {
+        let defaults = from_str(r#"{"port": 80, "log": {"level": "info", "file": "a.log"}}"#);
+        let env = from_str(r#"{"port": 8080, "log": {"level": "debug"}}"#);
+        let user = from_str(r#"{"log": {"file": "b.log"}, "name": "me"}"#);
+
+        let merged = JsonObject::merge_all(vec![defaults.unwrap(), env.unwrap(), user.unwrap()]);
+        assert_eq!(
+            merged,
+            from_str(r#"{"port": 8080, "log": {"level": "debug", "file": "b.log"}, "name": "me"}"#)
+                .unwrap()
+        );
+        assert_eq!(JsonObject::merge_all(vec![]), JsonObject::Null);
+    }
+}
@@ -0,0 +1,7 @@
+pub mod access;
+pub mod build;
+pub mod compare;
+pub mod convert;
+pub mod merge;
+pub mod transform;
+pub mod traverse;
@@ -0,0 +1,456 @@
+use std::mem;
+
+use crate::parser::JsonObject;
+
+/// What `transform` is looking at: an object entry or an array element.
+#[derive(Debug, Clone, Copy)]
+pub struct TransformContext<'a> {
+    /// The entry's key, or `None` for array elements.
+    pub key: Option<&'a str>,
+    /// How far below the root the value is. Children of the root are at
+    /// depth 1.
+    pub depth: usize,
+    pub value: &'a JsonObject,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransformAction {
+    Keep,
+    /// Moves the entry to a new key. Treated as `Keep` for array elements.
+    Rename(String),
+    Replace(JsonObject),
+    Drop,
+}
+
+impl JsonObject {
+    /// Renames every object key in the document using `rename`.
+    ///
+    /// If several keys of one object are renamed to the same name, the entry
+    /// whose original key sorts last wins.
+    pub fn rename_keys<F: Fn(&str) -> String>(&mut self, rename: F) {
+        rename_keys(self, &rename);
+    }
+
+    /// Rewrites numbers into a canonical form so that equal values compare
+    /// equal: integral `Number` values in the `i64` range, including `-0.0`,
+    /// become `Integer`, and other numbers are left as they are.
+    pub fn normalize_numbers(&mut self) {
+        match self {
+            JsonObject::Object(elements) => elements
+                .values_mut()
+                .for_each(JsonObject::normalize_numbers),
+            JsonObject::Array(elements) => {
+                elements.iter_mut().for_each(JsonObject::normalize_numbers)
+            }
+            // `i64::MAX as f64` rounds up to 2^63, which is itself out of range.
+            JsonObject::Number(value)
+                if *value == value.trunc()
+                    && *value >= i64::MIN as f64
+                    && *value < i64::MAX as f64 =>
+            {
+                *self = JsonObject::Integer(*value as i64)
+            }
+            _ => {}
+        }
+    }
+
+    /// Replaces every object value for which `should_redact(key, value)`
+    /// returns true with a copy of `replacement`. Redacted values are not
+    /// searched any further.
+    pub fn redact<F: Fn(&str, &JsonObject) -> bool>(
+        &mut self,
+        should_redact: F,
+        replacement: JsonObject,
+    ) {
+        redact(self, &should_redact, &replacement);
+    }
+
+    /// Decides the fate of every object entry and array element in one pass,
+    /// parents before their children. Kept and renamed values are then
+    /// transformed in turn; replacements are inserted as they are.
+    ///
+    /// As with `rename_keys`, when renamed keys collide the entry whose
+    /// original key sorts last wins.
+    pub fn transform<F: Fn(TransformContext) -> TransformAction>(&mut self, f: F) {
+        transform(self, &f, 1);
+    }
+
+    /// Replaces every array or object nested more than `max_depth` levels
+    /// below `self` with the string `"…"`. Scalars are kept at any depth
+    /// their parent survives to.
+    pub fn truncate_depth(&mut self, max_depth: usize) {
+        truncate_depth(self, max_depth, 0);
+    }
+
+    /// Empties the value in place, keeping any allocated capacity:
+    ///
+    /// - arrays and objects lose all their elements
+    /// - strings become empty
+    /// - numbers become `0`
+    /// - booleans and `Null` become `Null`
+    pub fn clear(&mut self) {
+        match self {
+            JsonObject::Object(elements) => elements.clear(),
+            JsonObject::Array(elements) => elements.clear(),
+            JsonObject::String(value) => value.clear(),
+            JsonObject::Integer(value) => *value = 0,
+            JsonObject::Number(value) => *value = 0.0,
+            JsonObject::WideInteger(value) => *value = 0,
+            JsonObject::Boolean(_) | JsonObject::Null => *self = JsonObject::Null,
+        }
+    }
+}
+
+fn redact<F: Fn(&str, &JsonObject) -> bool>(
+    object: &mut JsonObject,
+    should_redact: &F,
+    replacement: &JsonObject,
+) {
+    match object {
+        JsonObject::Object(elements) => {
+            for (key, value) in elements.iter_mut() {
+                if should_redact(key, value) {
+                    *value = replacement.clone();
+                } else {
+                    redact(value, should_redact, replacement);
+                }
+            }
+        }
+        JsonObject::Array(elements) => {
+            for element in elements {
+                redact(element, should_redact, replacement);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn truncate_depth(object: &mut JsonObject, max_depth: usize, depth: usize) {
+    match object {
+        JsonObject::Object(_) | JsonObject::Array(_) if depth > max_depth => {
+            *object = JsonObject::String("\u{2026}".to_string());
+        }
+        JsonObject::Object(elements) => elements
+            .values_mut()
+            .for_each(|value| truncate_depth(value, max_depth, depth + 1)),
+        JsonObject::Array(elements) => elements
+            .iter_mut()
+            .for_each(|element| truncate_depth(element, max_depth, depth + 1)),
+        _ => {}
+    }
+}
+
+fn transform<F: Fn(TransformContext) -> TransformAction>(
+    object: &mut JsonObject,
+    f: &F,
+    depth: usize,
+) {
+    match object {
+        JsonObject::Object(elements) => {
+            let mut entries: Vec<_> = mem::take(elements).into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+            for (key, mut value) in entries {
+                let action = f(TransformContext {
+                    key: Some(&key),
+                    depth,
+                    value: &value,
+                });
+                let key = match action {
+                    TransformAction::Keep => key,
+                    TransformAction::Rename(new_key) => new_key,
+                    TransformAction::Replace(replacement) => {
+                        elements.insert(key, replacement);
+                        continue;
+                    }
+                    TransformAction::Drop => continue,
+                };
+
+                transform(&mut value, f, depth + 1);
+                elements.insert(key, value);
+            }
+        }
+        JsonObject::Array(elements) => {
+            for mut element in mem::take(elements) {
+                let action = f(TransformContext {
+                    key: None,
+                    depth,
+                    value: &element,
+                });
+                match action {
+                    TransformAction::Keep | TransformAction::Rename(_) => {
+                        transform(&mut element, f, depth + 1);
+                        elements.push(element);
+                    }
+                    TransformAction::Replace(replacement) => elements.push(replacement),
+                    TransformAction::Drop => {}
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn rename_keys<F: Fn(&str) -> String>(object: &mut JsonObject, rename: &F) {
+    match object {
+        JsonObject::Object(elements) => {
+            let mut entries: Vec<_> = mem::take(elements).into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+            for (key, mut value) in entries {
+                rename_keys(&mut value, rename);
+                elements.insert(rename(&key), value);
+            }
+        }
+        JsonObject::Array(elements) => {
+            for element in elements {
+                rename_keys(element, rename);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    fn snake_case(key: &str) -> String {
+        let mut output = String::new();
+        for char in key.chars() {
+            if char.is_ascii_uppercase() {
+                output.push('_');
+                output.push(char.to_ascii_lowercase());
+            } else {
+                output.push(char);
+            }
+        }
+        output
+    }
+
+    #[test]
+    fn renames_nested_keys() {
+        let mut inner = HashMap::new();
+        inner.insert("lastName".to_string(), JsonObject::Null);
+        let mut map = HashMap::new();
+        map.insert("firstName".to_string(), JsonObject::Object(inner));
+        map.insert(
+            "userList".to_string(),
+            JsonObject::Array(vec![JsonObject::Object(HashMap::from([(
+                "isAdmin".to_string(),
+                JsonObject::Boolean(true),
+            )]))]),
+        );
+        let mut json = JsonObject::Object(map);
+
+        json.rename_keys(snake_case);
+
+        let mut inner = HashMap::new();
+        inner.insert("last_name".to_string(), JsonObject::Null);
+        let mut map = HashMap::new();
+        map.insert("first_name".to_string(), JsonObject::Object(inner));
+        map.insert(
+            "user_list".to_string(),
+            JsonObject::Array(vec![JsonObject::Object(HashMap::from([(
+                "is_admin".to_string(),
+                JsonObject::Boolean(true),
+            )]))]),
+        );
+        assert_eq!(json, JsonObject::Object(map));
+    }
+
+    #[test]
+    fn transforms_keys_and_values_in_one_pass() {
+        let mut json = crate::parser::from_str(
+            r#"{"userName": "ada", "apiToken": "secret", "loginHistory": [{"ipAddress": "::1", "apiToken": "old"}, null]}"#,
+        )
+        .unwrap();
+
+        json.transform(|context| match context.key {
+            Some("apiToken") => TransformAction::Replace(JsonObject::Null),
+            Some(key) => TransformAction::Rename(snake_case(key)),
+            None if context.value == &JsonObject::Null => TransformAction::Drop,
+            None => TransformAction::Keep,
+        });
+
+        assert_eq!(
+            json,
+            crate::parser::from_str(
+                r#"{"user_name": "ada", "apiToken": null, "login_history": [{"ip_address": "::1", "apiToken": null}]}"#
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn transform_reports_depth() {
+        let mut json = crate::parser::from_str(r#"{"a": {"b": [{"c": 1}]}, "d": 2}"#).unwrap();
+
+        json.transform(|context| match context.depth {
+            4 => TransformAction::Replace(JsonObject::Boolean(true)),
+            _ => TransformAction::Keep,
+        });
+
+        assert_eq!(
+            json,
+            crate::parser::from_str(r#"{"a": {"b": [{"c": true}]}, "d": 2}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn normalizes_negative_zero_and_integral_floats() {
+        let mut json = JsonObject::Array(vec![
+            JsonObject::Number(-0.0),
+            JsonObject::Object(HashMap::from([("a".to_string(), JsonObject::Number(-0.0))])),
+            JsonObject::Number(3.0),
+            JsonObject::Number(-2.5),
+            JsonObject::Number(1e20),
+            JsonObject::Number(f64::INFINITY),
+            JsonObject::Integer(7),
+        ]);
+
+        json.normalize_numbers();
+
+        let JsonObject::Array(elements) = &json else {
+            unreachable!()
+        };
+        assert!(matches!(elements[0], JsonObject::Integer(0)));
+        assert!(matches!(
+            &elements[1],
+            JsonObject::Object(entries) if matches!(entries["a"], JsonObject::Integer(0))
+        ));
+        assert!(matches!(elements[2], JsonObject::Integer(3)));
+        assert!(matches!(elements[3], JsonObject::Number(value) if value == -2.5));
+        assert!(matches!(elements[4], JsonObject::Number(value) if value == 1e20));
+        assert!(matches!(elements[5], JsonObject::Number(value) if value.is_infinite()));
+        assert!(matches!(elements[6], JsonObject::Integer(7)));
+    }
+
+    #[test]
+    fn truncates_deep_structures() {
+        let mut json = crate::parser::from_str(
+            r#"{"a": {"b": {"c": {"d": {"e": 1}}}, "n": 2}, "x": [[3], 4]}"#,
+        )
+        .unwrap();
+
+        json.truncate_depth(2);
+        assert_eq!(
+            json,
+            crate::parser::from_str(r#"{"a": {"b": {"c": "\u2026"}, "n": 2}, "x": [[3], 4]}"#)
+                .unwrap()
+        );
+
+        json.truncate_depth(0);
+        let JsonObject::Object(map) = &json else {
+            unreachable!()
+        };
+        assert_eq!(map["a"], JsonObject::String("\u{2026}".to_string()));
+        assert_eq!(map["x"], JsonObject::String("\u{2026}".to_string()));
+
+        let mut scalar = JsonObject::Number(1.0);
+        scalar.truncate_depth(0);
+        assert_eq!(scalar, JsonObject::Number(1.0));
+    }
+
+    #[test]
+    fn clears_containers_keeping_capacity() {
+        let mut json = JsonObject::Array(Vec::with_capacity(8));
+        json.ensure_array().push(JsonObject::Number(1.0));
+        json.clear();
+        let JsonObject::Array(elements) = &json else {
+            unreachable!()
+        };
+        assert!(elements.is_empty());
+        assert!(elements.capacity() >= 8);
+
+        let mut json = JsonObject::Object(HashMap::from([
+            ("a".to_string(), JsonObject::Null),
+            ("b".to_string(), JsonObject::Null),
+        ]));
+        let JsonObject::Object(elements) = &json else {
+            unreachable!()
+        };
+        let capacity = elements.capacity();
+        json.clear();
+        let JsonObject::Object(elements) = &json else {
+            unreachable!()
+        };
+        assert!(elements.is_empty());
+        assert_eq!(elements.capacity(), capacity);
+    }
+
+    #[test]
+    fn clears_scalars() {
+        let mut json = JsonObject::String(String::with_capacity(16));
+        json.clear();
+        assert_eq!(json, JsonObject::String(String::new()));
+
+        let mut json = JsonObject::Number(42.0);
+        json.clear();
+        assert!(matches!(json, JsonObject::Number(value) if value == 0.0));
+
+        let mut json = JsonObject::Integer(42);
+        json.clear();
+        assert!(matches!(json, JsonObject::Integer(0)));
+
+        let mut json = JsonObject::WideInteger(i128::MAX);
+        json.clear();
+        assert!(matches!(json, JsonObject::WideInteger(0)));
+
+        let mut json = JsonObject::Boolean(true);
+        json.clear();
+        assert_eq!(json, JsonObject::Null);
+    }
+
+    #[test]
+    fn redacts_sensitive_keys() {
+        let user = |password: &str| {
+            JsonObject::Object(HashMap::from([
+                ("name".to_string(), JsonObject::String("ada".to_string())),
+                (
+                    "password".to_string(),
+                    JsonObject::String(password.to_string()),
+                ),
+                (
+                    "ssn".to_string(),
+                    JsonObject::Object(HashMap::from([(
+                        "number".to_string(),
+                        JsonObject::Number(123.0),
+                    )])),
+                ),
+            ]))
+        };
+        let mut json = JsonObject::Array(vec![user("hunter2"), user("letmein")]);
+
+        json.redact(
+            |key, _| key == "password" || key == "ssn",
+            JsonObject::String("***".to_string()),
+        );
+
+        let redacted = JsonObject::Object(HashMap::from([
+            ("name".to_string(), JsonObject::String("ada".to_string())),
+            (
+                "password".to_string(),
+                JsonObject::String("***".to_string()),
+            ),
+            ("ssn".to_string(), JsonObject::String("***".to_string())),
+        ]));
+        assert_eq!(json, JsonObject::Array(vec![redacted.clone(), redacted]));
+    }
+
+    #[test]
+    fn rename_collision_keeps_last_sorted_key() {
+        let mut map = HashMap::new();
+        map.insert("a".to_string(), JsonObject::Number(1.0));
+        map.insert("b".to_string(), JsonObject::Number(2.0));
+        let mut json = JsonObject::Object(map);
+
+        json.rename_keys(|_| "same".to_string());
+
+        let mut map = HashMap::new();
+        map.insert("same".to_string(), JsonObject::Number(2.0));
+        assert_eq!(json, JsonObject::Object(map));
+    }
+}
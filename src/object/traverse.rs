@@ -0,0 +1,336 @@
+use std::mem::size_of;
+
+use crate::parser::JsonObject;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PathSegment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+impl JsonObject {
+    /// Estimates the heap memory owned by this value and its children, in
+    /// bytes. The value itself is not counted, since it may live on the stack.
+    pub fn deep_size_bytes(&self) -> usize {
+        match self {
+            JsonObject::Object(elements) => {
+                // HashMap stores its entries inline plus one control byte each.
+                let table = elements.capacity() * (size_of::<(String, JsonObject)>() + 1);
+                let entries: usize = elements
+                    .iter()
+                    .map(|(key, value)| key.capacity() + value.deep_size_bytes())
+                    .sum();
+                table + entries
+            }
+            JsonObject::Array(elements) => {
+                let buffer = elements.capacity() * size_of::<JsonObject>();
+                let children: usize = elements.iter().map(JsonObject::deep_size_bytes).sum();
+                buffer + children
+            }
+            JsonObject::String(value) => value.capacity(),
+            JsonObject::Integer(_)
+            | JsonObject::Number(_)
+            | JsonObject::WideInteger(_)
+            | JsonObject::Boolean(_)
+            | JsonObject::Null => 0,
+        }
+    }
+
+    /// Counts the nodes in the document, including `self`, that satisfy
+    /// `predicate`.
+    pub fn count_matching<F: Fn(&JsonObject) -> bool>(&self, predicate: F) -> usize {
+        count_matching(self, &predicate)
+    }
+
+    /// Collects every node in the document, including `self`, that satisfies
+    /// `predicate`, parents before their children.
+    pub fn find_all<F: Fn(&JsonObject) -> bool>(&self, predicate: F) -> Vec<&JsonObject> {
+        let mut found = vec![];
+        self.walk_with_path(|_, node| {
+            if predicate(node) {
+                found.push(node);
+            }
+        });
+        found
+    }
+
+    /// Adds up every number in the document. Integers outside the `f64`
+    /// range of exact values are rounded.
+    pub fn sum_numbers(&self) -> f64 {
+        self.fold(0.0, |sum, node| match node {
+            JsonObject::Integer(value) => sum + *value as f64,
+            JsonObject::Number(value) => sum + value,
+            JsonObject::WideInteger(value) => sum + *value as f64,
+            _ => sum,
+        })
+    }
+
+    /// Collects every string value in the document, parents before their
+    /// children. Object keys are not included.
+    pub fn collect_strings(&self) -> Vec<&str> {
+        self.find_all(|node| matches!(node, JsonObject::String(_)))
+            .into_iter()
+            .filter_map(|node| match node {
+                JsonObject::String(value) => Some(value.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Calls `visit` for every node in the document, parents before their
+    /// children, along with the path leading to it from `self`.
+    pub fn walk_with_path<'a, F: FnMut(&[PathSegment<'a>], &'a JsonObject)>(
+        &'a self,
+        mut visit: F,
+    ) {
+        walk_with_path(self, &mut vec![], &mut visit);
+    }
+
+    /// Lists the JSON Pointer (RFC 6901) of every scalar in the document, in
+    /// sorted order. Empty arrays and objects have no leaves.
+    pub fn leaf_paths(&self) -> Vec<String> {
+        let mut paths = vec![];
+        self.walk_with_path(|path, node| {
+            if !matches!(node, JsonObject::Object(_) | JsonObject::Array(_)) {
+                paths.push(to_pointer(path));
+            }
+        });
+        paths.sort();
+        paths
+    }
+
+    /// Threads an accumulator through every node in the document, parents
+    /// before their children, starting from `init`.
+    pub fn fold<B, F: FnMut(B, &JsonObject) -> B>(&self, init: B, mut f: F) -> B {
+        fold(self, init, &mut f)
+    }
+}
+
+fn fold<B, F: FnMut(B, &JsonObject) -> B>(object: &JsonObject, init: B, f: &mut F) -> B {
+    let acc = f(init, object);
+
+    match object {
+        JsonObject::Object(elements) => elements
+            .values()
+            .fold(acc, |acc, value| fold(value, acc, f)),
+        JsonObject::Array(elements) => elements
+            .iter()
+            .fold(acc, |acc, element| fold(element, acc, f)),
+        _ => acc,
+    }
+}
+
+fn to_pointer(path: &[PathSegment]) -> String {
+    let mut pointer = String::new();
+    for segment in path {
+        pointer.push('/');
+        match segment {
+            PathSegment::Key(key) => pointer.push_str(&key.replace('~', "~0").replace('/', "~1")),
+            PathSegment::Index(i) => pointer.push_str(&i.to_string()),
+        }
+    }
+    pointer
+}
+
+fn walk_with_path<'a, F: FnMut(&[PathSegment<'a>], &'a JsonObject)>(
+    object: &'a JsonObject,
+    path: &mut Vec<PathSegment<'a>>,
+    visit: &mut F,
+) {
+    visit(path, object);
+
+    match object {
+        JsonObject::Object(elements) => {
+            for (key, value) in elements {
+                path.push(PathSegment::Key(key));
+                walk_with_path(value, path, visit);
+                path.pop();
+            }
+        }
+        JsonObject::Array(elements) => {
+            for (i, element) in elements.iter().enumerate() {
+                path.push(PathSegment::Index(i));
+                walk_with_path(element, path, visit);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+fn count_matching<F: Fn(&JsonObject) -> bool>(object: &JsonObject, predicate: &F) -> usize {
+    let children = match object {
+        JsonObject::Object(elements) => elements
+            .values()
+            .map(|value| count_matching(value, predicate))
+            .sum(),
+        JsonObject::Array(elements) => elements
+            .iter()
+            .map(|element| count_matching(element, predicate))
+            .sum(),
+        _ => 0,
+    };
+
+    children + usize::from(predicate(object))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn counts_matching_nodes() {
+        let json = JsonObject::Object(HashMap::from([
+            ("a".to_string(), JsonObject::Boolean(true)),
+            (
+                "b".to_string(),
+                JsonObject::Array(vec![
+                    JsonObject::Boolean(false),
+                    JsonObject::Null,
+                    JsonObject::Object(HashMap::from([(
+                        "c".to_string(),
+                        JsonObject::Boolean(true),
+                    )])),
+                ]),
+            ),
+        ]));
+
+        assert_eq!(
+            json.count_matching(|node| matches!(node, JsonObject::Boolean(_))),
+            3
+        );
+        assert_eq!(
+            json.count_matching(|node| matches!(node, JsonObject::Object(_))),
+            2
+        );
+        assert_eq!(json.count_matching(|_| false), 0);
+    }
+
+    #[test]
+    fn walks_with_paths() {
+        let json = JsonObject::Object(HashMap::from([
+            ("a".to_string(), JsonObject::Number(1.0)),
+            (
+                "b".to_string(),
+                JsonObject::Array(vec![
+                    JsonObject::String("x".to_string()),
+                    JsonObject::Object(HashMap::from([("c".to_string(), JsonObject::Number(2.0))])),
+                ]),
+            ),
+        ]));
+
+        let mut paths = vec![];
+        json.walk_with_path(|path, node| {
+            if let JsonObject::Number(_) = node {
+                paths.push(path.to_vec());
+            }
+        });
+        paths.sort_by_key(|path| path.len());
+
+        assert_eq!(
+            paths,
+            vec![
+                vec![PathSegment::Key("a")],
+                vec![
+                    PathSegment::Key("b"),
+                    PathSegment::Index(1),
+                    PathSegment::Key("c")
+                ],
+            ]
+        );
+    }
+
+    #[test]
+    fn folds_over_all_nodes() {
+        let json = crate::parser::from_str(r#"{"a": 1, "b": [2, {"c": 3.5}], "d": "4"}"#).unwrap();
+
+        let sum = json.fold(0.0, |sum, node| match node {
+            JsonObject::Integer(value) => sum + *value as f64,
+            JsonObject::Number(value) => sum + value,
+            _ => sum,
+        });
+        assert_eq!(sum, 6.5);
+
+        assert_eq!(json.fold(0, |count, _| count + 1), 7);
+
+        let mut order = vec![];
+        JsonObject::Array(vec![JsonObject::Array(vec![JsonObject::Null])])
+            .fold((), |_, node| order.push(node.clone()));
+        assert_eq!(order[2], JsonObject::Null);
+    }
+
+    #[test]
+    fn finds_all_matching_nodes() {
+        let json = crate::parser::from_str(r#"["a", {"b": ["c", 1, {"d": "e"}]}, null]"#).unwrap();
+
+        let mut strings: Vec<_> = json
+            .find_all(|node| matches!(node, JsonObject::String(_)))
+            .into_iter()
+            .map(|node| match node {
+                JsonObject::String(value) => value.as_str(),
+                _ => unreachable!(),
+            })
+            .collect();
+        strings.sort();
+        assert_eq!(strings, ["a", "c", "e"]);
+
+        assert_eq!(json.find_all(|node| node == &json), vec![&json]);
+        assert!(json.find_all(|_| false).is_empty());
+    }
+
+    #[test]
+    fn deep_size_bytes_of_scalars() {
+        assert_eq!(JsonObject::Null.deep_size_bytes(), 0);
+        assert_eq!(JsonObject::Number(42.69).deep_size_bytes(), 0);
+        assert_eq!(
+            JsonObject::String(String::with_capacity(16)).deep_size_bytes(),
+            16
+        );
+    }
+
+    #[test]
+    fn deep_size_bytes_of_containers() {
+        let mut elements = Vec::with_capacity(4);
+        elements.push(JsonObject::String(String::with_capacity(8)));
+        elements.push(JsonObject::Boolean(true));
+        let array = JsonObject::Array(elements);
+        assert_eq!(array.deep_size_bytes(), 4 * size_of::<JsonObject>() + 8);
+
+        let mut map = HashMap::new();
+        map.insert("key".to_string(), array);
+        let object = JsonObject::Object(map);
+        let size = object.deep_size_bytes();
+        assert!(size >= size_of::<(String, JsonObject)>() + 3 + 4 * size_of::<JsonObject>() + 8);
+        assert!(size < 1024);
+    }
+
+    #[test]
+    fn lists_leaf_pointers() {
+        let json = crate::parser::from_str(
+            r#"{"a": 1, "b": [true, {"c/d": null, "e~": "x"}, []], "f": {}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(json.leaf_paths(), ["/a", "/b/0", "/b/1/c~1d", "/b/1/e~0"]);
+        assert_eq!(JsonObject::Null.leaf_paths(), [""]);
+    }
+
+    #[test]
+    fn aggregates_numbers_and_strings() {
+        let json = crate::parser::from_str(
+            r#"{"a": 1, "b": ["x", 2.5, {"c": -0.5, "d": "y"}], "e": [true, null, "z"], "f": "w"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(json.sum_numbers(), 3.0);
+        let mut strings = json.collect_strings();
+        strings.sort();
+        assert_eq!(strings, ["w", "x", "y", "z"]);
+
+        let json = crate::parser::from_str(r#"["only", ["nested"]]"#).unwrap();
+        assert_eq!(json.sum_numbers(), 0.0);
+        assert_eq!(json.collect_strings(), ["only", "nested"]);
+    }
+}
@@ -0,0 +1,210 @@
+//! A representative subset of JSONTestSuite
+//! (https://github.com/nst/JSONTestSuite), named after its files. `y_` cases
+//! must parse, `n_` cases must fail in strict mode and `i_` cases pin down
+//! the behavior chosen where RFC 8259 leaves it to the parser.
+
+use super::{
+    from_str, from_str_with_options, options::ParseOptions, token::ParseError, JsonObject,
+};
+
+const ACCEPTED: &[(&str, &str)] = &[
+    ("y_array_arraysWithSpaces", "[[]   ]"),
+    ("y_array_empty", "[]"),
+    ("y_array_empty-string", r#"[""]"#),
+    ("y_array_heterogeneous", r#"[null, 1, "1", {}]"#),
+    ("y_array_with_leading_space", " [1]"),
+    ("y_array_with_trailing_space", "[2] "),
+    ("y_number", "[123e65]"),
+    ("y_number_0e+1", "[0e+1]"),
+    ("y_number_after_space", "[ 4]"),
+    ("y_number_int_with_exp", "[20e1]"),
+    ("y_number_minus_zero", "[-0]"),
+    ("y_number_negative_int", "[-123]"),
+    ("y_number_real_capital_e", "[1E22]"),
+    ("y_number_real_capital_e_neg_exp", "[1E-2]"),
+    ("y_number_real_capital_e_pos_exp", "[1E+2]"),
+    ("y_number_real_fraction_exponent", "[123.456e78]"),
+    ("y_number_simple_real", "[123.456789]"),
+    ("y_object_basic", r#"{"asd":"sdf"}"#),
+    ("y_object_duplicated_key", r#"{"a":"b","a":"c"}"#),
+    ("y_object_empty_key", r#"{"":0}"#),
+    (
+        "y_object_extreme_numbers",
+        r#"{ "min": -1.0e+28, "max": 1.0e+28 }"#,
+    ),
+    ("y_object_with_newlines", "{\n\"a\": \"b\"\n}"),
+    ("y_string_allowed_escapes", r#"["\"\\\/\b\f\n\r\t"]"#),
+    ("y_string_escaped_null", r#"["\u0000"]"#),
+    ("y_string_nonCharacterInUTF-8_U+FFFF", "[\"\u{ffff}\"]"),
+    ("y_string_surrogates_U+1D11E", r#"["\uD834\uDd1e"]"#),
+    ("y_string_unicode_escaped_double_quote", r#"["\u0022"]"#),
+    ("y_string_utf8", "[\"\u{20ac}\u{1d11e}\"]"),
+    ("y_string_with_del_character", "[\"a\u{7f}a\"]"),
+    ("y_structure_lonely_int", "42"),
+    ("y_structure_lonely_negative_real", "-0.1"),
+    ("y_structure_lonely_null", "null"),
+    ("y_structure_lonely_string", r#""asd""#),
+    ("y_structure_lonely_true", "true"),
+    ("y_structure_trailing_newline", "[\"a\"]\n"),
+    ("y_structure_whitespace_array", " [] "),
+    ("y_structure_whitespace_carriage_return", "[\r1\r\n]"),
+];
+
+const REJECTED: &[(&str, &str)] = &[
+    ("n_array_1_true_without_comma", "[1 true]"),
+    ("n_array_colon_instead_of_comma", r#"["": 1]"#),
+    ("n_array_comma_after_close", r#"[""],"#),
+    ("n_array_double_comma", "[1,,2]"),
+    ("n_array_extra_close", r#"["x"]]"#),
+    ("n_array_extra_comma", r#"["",]"#),
+    ("n_array_just_comma", "[,]"),
+    ("n_array_unclosed", r#"[""#),
+    ("n_incomplete_false", "[fals]"),
+    ("n_incomplete_null", "[nul]"),
+    ("n_incomplete_true", "[tru]"),
+    ("n_number_+1", "[+1]"),
+    ("n_number_-01", "[-01]"),
+    ("n_number_-1.0.", "[-1.0.]"),
+    ("n_number_-2.", "[-2.]"),
+    ("n_number_.-1", "[.-1]"),
+    ("n_number_.2e-3", "[.2e-3]"),
+    ("n_number_0.e1", "[0.e1]"),
+    ("n_number_0_capital_E", "[0E]"),
+    ("n_number_0_capital_E+", "[0E+]"),
+    ("n_number_0e", "[0e]"),
+    ("n_number_1.0e-", "[1.0e-]"),
+    ("n_number_1eE2", "[1eE2]"),
+    ("n_number_2.e3", "[2.e3]"),
+    ("n_number_9.e+", "[9.e+]"),
+    ("n_number_Inf", "[Inf]"),
+    ("n_number_NaN", "[NaN]"),
+    ("n_number_hex_1_digit", "[0x1]"),
+    ("n_number_infinity", "[Infinity]"),
+    ("n_number_minus_infinity", "[-Infinity]"),
+    ("n_number_minus_sign_with_trailing_garbage", "[-foo]"),
+    ("n_number_minus_space_1", "[- 1]"),
+    ("n_number_neg_int_starting_with_zero", "[-012]"),
+    ("n_number_neg_real_without_int_part", "[-.123]"),
+    ("n_number_real_without_fractional_part", "[1.]"),
+    ("n_number_starting_with_dot", "[.123]"),
+    ("n_number_with_alpha", "[1.2a-3]"),
+    ("n_number_with_leading_zero", "[012]"),
+    ("n_object_bad_value", r#"["x", truth]"#),
+    ("n_object_boolean_key", "{true:1}"),
+    ("n_object_comma_instead_of_colon", r#"{"x", null}"#),
+    ("n_object_missing_colon", r#"{"a" b}"#),
+    ("n_object_missing_key", r#"{:"b"}"#),
+    ("n_object_missing_value", r#"{"a":"#),
+    ("n_object_no-colon", r#"{"a""#),
+    ("n_object_non_string_key", "{1:1}"),
+    (
+        "n_object_non_string_key_but_huge_number_instead",
+        "{9999E9999:1}",
+    ),
+    ("n_object_single_quote", "{'a':0}"),
+    ("n_object_trailing_comma", r#"{"id":0,}"#),
+    ("n_object_unquoted_key", r#"{a: "b"}"#),
+    ("n_object_with_trailing_garbage", r#"{"a": true} "x""#),
+    ("n_string_1_surrogate_then_escape", r#"["\uD800\"]"#),
+    ("n_string_escape_x", r#"["\x00"]"#),
+    ("n_string_escaped_emoji", "[\"\\\u{1f300}\"]"),
+    ("n_string_incomplete_escaped_character", r#"["\u00A"]"#),
+    ("n_string_invalid_backslash_esc", r#"["\a"]"#),
+    ("n_string_invalid_unicode_escape", r#"["\uqqqq"]"#),
+    ("n_string_single_quote", "['single quote']"),
+    ("n_string_start_escape_unclosed", r#"["\"#),
+    ("n_string_unescaped_ctrl_char", "[\"a\u{0}a\"]"),
+    ("n_string_unescaped_newline", "[\"new\nline\"]"),
+    ("n_string_unescaped_tab", "[\"\t\"]"),
+    ("n_structure_UTF8_BOM_no_data", "\u{feff}"),
+    ("n_structure_angle_bracket_.", "<.>"),
+    ("n_structure_close_unopened_array", "1]"),
+    ("n_structure_comment", "[1] // comment"),
+    ("n_structure_double_array", "[][]"),
+    ("n_structure_no_data", ""),
+    ("n_structure_null-byte-outside-string", "[\u{0}]"),
+    ("n_structure_open_object_close_array", r#"{"a": 1]"#),
+    ("n_structure_unclosed_array_unfinished_true", "[ false, tru"),
+    ("n_structure_unicode-identifier", "\u{e5}"),
+    ("n_structure_whitespace_U+2060_word_joiner", "[\u{2060}]"),
+    ("n_structure_whitespace_formfeed", "[\u{c}]"),
+    ("n_structure_whitespace_only", " \n"),
+];
+
+fn strict() -> ParseOptions {
+    ParseOptions {
+        strict: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn accepts_y_cases() {
+    let failures: Vec<_> = ACCEPTED
+        .iter()
+        .filter(|(_, input)| {
+            from_str(input).is_err() || from_str_with_options(input, &strict()).is_err()
+        })
+        .map(|(name, _)| name)
+        .collect();
+
+    assert!(failures.is_empty(), "{:?}", failures);
+}
+
+#[test]
+fn rejects_n_cases() {
+    let failures: Vec<_> = REJECTED
+        .iter()
+        .filter(|(_, input)| from_str_with_options(input, &strict()).is_ok())
+        .map(|(name, _)| name)
+        .collect();
+
+    assert!(failures.is_empty(), "{:?}", failures);
+}
+
+#[test]
+fn pins_down_i_cases() {
+    let parse = |input| from_str_with_options(input, &strict());
+    let array = |value| Ok(JsonObject::Array(vec![value]));
+
+    assert_eq!(
+        parse("[0.4e00669999999999999999999999999999999999999999999999]"),
+        array(JsonObject::Number(f64::INFINITY)),
+        "i_number_huge_exp"
+    );
+    assert_eq!(
+        parse("[-1e+9999]"),
+        array(JsonObject::Number(f64::NEG_INFINITY)),
+        "i_number_neg_int_huge_exp"
+    );
+    assert_eq!(
+        parse("[123e-10000000]"),
+        array(JsonObject::Number(0.0)),
+        "i_number_real_underflow"
+    );
+    assert_eq!(
+        parse("[100000000000000000000]"),
+        array(JsonObject::Number(1e20)),
+        "i_number_too_big_pos_int"
+    );
+    assert_eq!(
+        parse(r#"["\uDADA"]"#),
+        Err(ParseError::InvalidUnicodeEscape),
+        "i_string_1st_surrogate_but_2nd_missing"
+    );
+    assert_eq!(
+        parse(r#"{"\uDFAA":0}"#),
+        Err(ParseError::InvalidUnicodeEscape),
+        "i_object_key_lone_2nd_surrogate"
+    );
+    assert_eq!(
+        parse("\u{feff}{}"),
+        Err(ParseError::InvalidToken),
+        "i_structure_UTF-8_BOM_empty_object"
+    );
+    assert_eq!(
+        parse(&("[".repeat(500) + &"]".repeat(500))),
+        Err(ParseError::DepthLimitExceeded),
+        "i_structure_500_nested_arrays"
+    );
+}
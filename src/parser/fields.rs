@@ -0,0 +1,180 @@
+use std::str::Chars;
+
+use super::{
+    options::ParseOptions,
+    parse_tokens_with_options,
+    token::{ParseError, Token, Tokenizer},
+    JsonObject,
+};
+
+/// Lazily yields the fields of a top-level JSON object.
+///
+/// Only the tokens of the field being read are held in memory. Since fields
+/// are not collected, duplicate keys are yielded as they appear.
+pub struct ObjectFields<'a> {
+    tokens: Tokenizer<Chars<'a>>,
+    started: bool,
+    done: bool,
+}
+
+/// Starts reading `input` as an object, one key/value pair at a time.
+///
+/// Fails straight away if `input` does not begin with `{`.
+pub fn parse_object_fields(input: &str) -> Result<ObjectFields<'_>, ParseError> {
+    let mut tokens = Tokenizer::new(input.chars(), ParseOptions::default());
+
+    match tokens.next().transpose()? {
+        Some(Token::OpenBrace) => Ok(ObjectFields {
+            tokens,
+            started: false,
+            done: false,
+        }),
+        Some(token) => Err(ParseError::UnexpectedToken(token)),
+        None => Err(ParseError::UnexpectedEndOfInput),
+    }
+}
+
+impl ObjectFields<'_> {
+    fn next_token(&mut self) -> Result<Token, ParseError> {
+        self.tokens
+            .next()
+            .transpose()?
+            .ok_or(ParseError::UnexpectedEndOfInput)
+    }
+
+    fn next_field(&mut self) -> Result<Option<(String, JsonObject)>, ParseError> {
+        let mut token = self.next_token()?;
+        if let Token::CloseBrace = token {
+            return match self.tokens.next().transpose()? {
+                Some(token) => Err(ParseError::UnexpectedToken(token)),
+                None => Ok(None),
+            };
+        }
+
+        if self.started {
+            match token {
+                Token::Comma => match self.next_token()? {
+                    Token::CloseBrace => return Err(ParseError::TrailingComma),
+                    next => token = next,
+                },
+                token => return Err(ParseError::UnexpectedToken(token)),
+            }
+        }
+        self.started = true;
+
+        let key = match token {
+            Token::String(key) => key.to_string(),
+            token => return Err(ParseError::UnexpectedToken(token)),
+        };
+
+        if self.next_token()? != Token::Colon {
+            return Err(ParseError::ExpectedColon);
+        }
+
+        let value = self.read_value()?;
+        Ok(Some((key, value)))
+    }
+
+    /// Collects the tokens of a single value and parses them.
+    fn read_value(&mut self) -> Result<JsonObject, ParseError> {
+        let mut value = vec![];
+        let mut depth = 0;
+
+        loop {
+            let token = self.next_token()?;
+            match token {
+                Token::OpenBrace | Token::OpenBracket => depth += 1,
+                Token::CloseBrace | Token::CloseBracket if depth == 0 => {
+                    return Err(ParseError::UnexpectedToken(token));
+                }
+                Token::CloseBrace | Token::CloseBracket => depth -= 1,
+                _ => {}
+            }
+            value.push(token);
+
+            if depth == 0 {
+                return parse_tokens_with_options(value, &ParseOptions::default());
+            }
+        }
+    }
+}
+
+impl Iterator for ObjectFields<'_> {
+    type Item = Result<(String, JsonObject), ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let field = self.next_field().transpose();
+        if !matches!(field, Some(Ok(_))) {
+            self.done = true;
+        }
+        field
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn yields_fields_lazily() {
+        let input = r#"{"a": 1, "b": [true, {"c": null}], "d": "x"} "#;
+        let mut fields = parse_object_fields(input).unwrap();
+
+        assert_eq!(
+            fields.next(),
+            Some(Ok(("a".to_string(), JsonObject::Number(1.0))))
+        );
+        assert_eq!(
+            fields.next(),
+            Some(Ok((
+                "b".to_string(),
+                JsonObject::Array(vec![
+                    JsonObject::Boolean(true),
+                    JsonObject::object_of([("c".to_string(), JsonObject::Null)]),
+                ])
+            )))
+        );
+        assert_eq!(
+            fields.next(),
+            Some(Ok(("d".to_string(), JsonObject::String("x".to_string()))))
+        );
+        assert_eq!(fields.next(), None);
+        assert_eq!(fields.next(), None);
+
+        assert_eq!(parse_object_fields("{}").unwrap().count(), 0);
+    }
+
+    #[test]
+    fn stops_at_the_first_error() {
+        assert!(matches!(
+            parse_object_fields("[1]"),
+            Err(ParseError::UnexpectedToken(Token::OpenBracket))
+        ));
+        assert!(matches!(
+            parse_object_fields(""),
+            Err(ParseError::UnexpectedEndOfInput)
+        ));
+
+        let mut fields = parse_object_fields(r#"{"a": 1 "b": 2}"#).unwrap();
+        assert!(matches!(fields.next(), Some(Ok(_))));
+        assert_eq!(
+            fields.next(),
+            Some(Err(ParseError::UnexpectedToken(Token::String("b".into()))))
+        );
+        assert_eq!(fields.next(), None);
+
+        let mut fields = parse_object_fields(r#"{"a": [1"#).unwrap();
+        assert_eq!(fields.next(), Some(Err(ParseError::UnexpectedEndOfInput)));
+
+        let mut fields = parse_object_fields(r#"{"a": 1,}"#).unwrap();
+        assert!(matches!(fields.next(), Some(Ok(_))));
+        assert_eq!(fields.next(), Some(Err(ParseError::TrailingComma)));
+
+        let mut fields = parse_object_fields(r#"{"a" 1}"#).unwrap();
+        assert_eq!(fields.next(), Some(Err(ParseError::ExpectedColon)));
+    }
+}
@@ -1,136 +1,764 @@
-use std::{collections::HashMap, iter::Peekable, slice::Iter};
+use std::{collections::HashMap, io::Read, iter::Peekable, slice::Iter, sync::Arc};
 
-use self::token::Token;
+use self::{
+    options::ParseOptions,
+    token::{tokenize_located, tokenize_with_options, ParseError, PositionedError, Token},
+};
 
+#[cfg(test)]
+mod conformance;
+pub mod fields;
+pub mod options;
+pub mod stream;
 pub mod token;
 
-#[derive(Debug, PartialEq)]
+/// A parsed JSON value.
+///
+/// Equality compares objects as unordered maps: two objects are equal when
+/// they hold the same keys with equal values, whatever order the keys were
+/// inserted in. Arrays compare element by element, in order. Numbers compare
+/// by value whatever their variant, so `Integer(1)` equals `Number(1.0)`, but
+/// `Integer(9007199254740993)` does not equal the nearest `f64`.
+#[derive(Debug, Clone)]
 pub enum JsonObject {
     Object(HashMap<String, JsonObject>),
     Array(Vec<JsonObject>),
     String(String),
+    /// A number literal without a decimal point or exponent that fits in an
+    /// `i64`.
+    Integer(i64),
     Number(f64),
+    /// An integer outside the `i64` range. The parser only produces it with
+    /// `ParseOptions::wide_integers`; MessagePack decoding uses it for
+    /// `uint64` values above `i64::MAX`.
+    WideInteger(i128),
     Boolean(bool),
     Null,
 }
 
-pub fn parse_tokens(tokens: Vec<Token>) -> JsonObject {
-    parse(&mut tokens.iter().peekable())
+impl PartialEq for JsonObject {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (JsonObject::Object(a), JsonObject::Object(b)) => a == b,
+            (JsonObject::Array(a), JsonObject::Array(b)) => a == b,
+            (JsonObject::String(a), JsonObject::String(b)) => a == b,
+            (JsonObject::Integer(a), JsonObject::Integer(b)) => a == b,
+            (JsonObject::Number(a), JsonObject::Number(b)) => a == b,
+            (JsonObject::WideInteger(a), JsonObject::WideInteger(b)) => a == b,
+            (JsonObject::Integer(a), JsonObject::WideInteger(b))
+            | (JsonObject::WideInteger(b), JsonObject::Integer(a)) => i128::from(*a) == *b,
+            (JsonObject::Integer(integer), JsonObject::Number(float))
+            | (JsonObject::Number(float), JsonObject::Integer(integer)) => {
+                integer_eq_float((*integer).into(), *float)
+            }
+            (JsonObject::WideInteger(integer), JsonObject::Number(float))
+            | (JsonObject::Number(float), JsonObject::WideInteger(integer)) => {
+                integer_eq_float(*integer, *float)
+            }
+            (JsonObject::Boolean(a), JsonObject::Boolean(b)) => a == b,
+            (JsonObject::Null, JsonObject::Null) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Compares exactly, without rounding `integer` to the nearest `f64`.
+fn integer_eq_float(integer: i128, float: f64) -> bool {
+    // Rounds up to 2^127. Every integral f64 below it in magnitude converts
+    // to i128 without saturating.
+    const LIMIT: f64 = i128::MAX as f64;
+    float.fract() == 0.0 && float.abs() < LIMIT && float as i128 == integer
+}
+
+pub fn from_str(input: &str) -> Result<JsonObject, ParseError> {
+    from_str_with_options(input, &ParseOptions::default())
+}
+
+pub fn from_str_with_options(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<JsonObject, ParseError> {
+    let tokens = tokenize_with_options(input.to_string(), options)?;
+    parse_tokens_with_options(tokens, options)
+}
+
+/// Like `from_str_with_options`, but reports where in the input an error
+/// occurred. Errors found while tokenizing point at the character where they
+/// were detected, and errors found while building the tree at the first
+/// character of the offending token.
+pub fn from_str_with_position(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<JsonObject, PositionedError> {
+    let located = tokenize_located(input, options)?;
+    let mut tokens = located.tokens.iter().peekable();
+
+    parse_all(&mut tokens, options, &mut ParseStats::default()).map_err(|error| {
+        // Every error but running out of tokens is reported right after
+        // consuming the token it is about.
+        let consumed = located.tokens.len() - tokens.len();
+        let position = match error {
+            ParseError::UnexpectedEndOfInput => located.end,
+            _ => consumed
+                .checked_sub(1)
+                .map_or(located.end, |index| located.starts[index]),
+        };
+        PositionedError { error, position }
+    })
+}
+
+/// Facts about a document gathered while parsing it.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParseStats {
+    /// The most elements in any array or entries in any object. Entries
+    /// replaced by a later duplicate key are counted too.
+    pub max_width: usize,
+}
+
+/// Like `from_str_with_options`, but also returns `ParseStats` for the
+/// document.
+pub fn from_str_with_stats(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<(JsonObject, ParseStats), ParseError> {
+    let tokens = tokenize_with_options(input.to_string(), options)?;
+    let mut stats = ParseStats::default();
+    let json = parse_document(&tokens, options, &mut stats)?;
+    Ok((json, stats))
+}
+
+/// Parses `input` into a tree that can be shared between threads without
+/// cloning. `JsonObject` methods are available through the `Arc`.
+pub fn parse_shared(input: &str) -> Result<Arc<JsonObject>, ParseError> {
+    from_str(input).map(Arc::new)
+}
+
+/// Parses two documents and compares them structurally, ignoring
+/// whitespace and object key order.
+pub fn json_eq(a: &str, b: &str) -> Result<bool, ParseError> {
+    Ok(from_str(a)? == from_str(b)?)
 }
 
-fn parse(tokens: &mut Peekable<Iter<Token>>) -> JsonObject {
+impl JsonObject {
+    /// Reads and parses a document from `reader`, failing with
+    /// `ParseError::InputTooLarge` once more than `max_bytes` have been read.
+    pub fn try_from_reader<R: Read>(reader: R, max_bytes: usize) -> Result<JsonObject, ParseError> {
+        let mut bytes = vec![];
+        reader
+            .take((max_bytes as u64).saturating_add(1))
+            .read_to_end(&mut bytes)
+            .map_err(|error| ParseError::Io(error.kind()))?;
+        if bytes.len() > max_bytes {
+            return Err(ParseError::InputTooLarge);
+        }
+
+        let input = std::str::from_utf8(&bytes).map_err(|_| ParseError::InvalidUtf8)?;
+        from_str(input)
+    }
+}
+
+/// Builds a document from tokens. An empty token list is not a document and
+/// fails with `ParseError::UnexpectedEndOfInput`.
+pub fn parse_tokens(tokens: Vec<Token>) -> Result<JsonObject, ParseError> {
+    parse_tokens_with_options(tokens, &ParseOptions::default())
+}
+
+pub fn parse_tokens_with_options(
+    tokens: Vec<Token>,
+    options: &ParseOptions,
+) -> Result<JsonObject, ParseError> {
+    parse_document(&tokens, options, &mut ParseStats::default())
+}
+
+/// Parses `tokens` as a single value that uses up all of them.
+fn parse_document(
+    tokens: &[Token],
+    options: &ParseOptions,
+    stats: &mut ParseStats,
+) -> Result<JsonObject, ParseError> {
+    parse_all(&mut tokens.iter().peekable(), options, stats)
+}
+
+fn parse_all(
+    tokens: &mut Peekable<Iter<Token>>,
+    options: &ParseOptions,
+    stats: &mut ParseStats,
+) -> Result<JsonObject, ParseError> {
+    let json = parse(tokens, options, 0, stats)?;
+    if tokens.next().is_some() {
+        return Err(ParseError::TrailingTokens);
+    }
+    Ok(json)
+}
+
+fn parse(
+    tokens: &mut Peekable<Iter<Token>>,
+    options: &ParseOptions,
+    depth: usize,
+    stats: &mut ParseStats,
+) -> Result<JsonObject, ParseError> {
     match tokens.next() {
-        Some(token) => {
-            match token {
-                Token::Null => JsonObject::Null,
-                Token::Boolean(value) => JsonObject::Boolean(*value),
-                Token::Number(value) => JsonObject::Number(*value),
-                Token::String(value) => JsonObject::String(value.to_string()),
-                Token::OpenBracket => parse_array(tokens),
-                Token::OpenBrace => parse_object(tokens),
-                _ => todo!("{:?}", token),
+        Some(token) => match token {
+            Token::Null => Ok(JsonObject::Null),
+            Token::Boolean(value) => Ok(JsonObject::Boolean(*value)),
+            Token::Integer(value) => Ok(JsonObject::Integer(*value)),
+            Token::WideInteger(value) => Ok(JsonObject::WideInteger(*value)),
+            Token::Number(value) => Ok(JsonObject::Number(*value)),
+            Token::String(value) => Ok(JsonObject::String(value.to_string())),
+            Token::OpenBracket | Token::OpenBrace if depth >= options.max_depth => {
+                Err(ParseError::DepthLimitExceeded)
             }
-        }
-        None => JsonObject::Null,
+            Token::OpenBracket => parse_array(tokens, options, depth, stats),
+            Token::OpenBrace => parse_object(tokens, options, depth, stats),
+            _ => Err(ParseError::UnexpectedToken(token.clone())),
+        },
+        None => Err(ParseError::UnexpectedEndOfInput),
     }
 }
 
-fn parse_array(tokens: &mut Peekable<Iter<Token>>) -> JsonObject {
+fn parse_array(
+    tokens: &mut Peekable<Iter<Token>>,
+    options: &ParseOptions,
+    depth: usize,
+    stats: &mut ParseStats,
+) -> Result<JsonObject, ParseError> {
     let mut elements = vec![];
+    let mut after_comma = false;
 
-    while let Some(token) = tokens.peek() {
+    loop {
+        let token = *tokens.peek().ok_or(ParseError::UnexpectedEndOfInput)?;
         match token {
+            Token::CloseBracket if after_comma && !options.trailing_commas => {
+                tokens.next();
+                return Err(ParseError::TrailingComma);
+            }
             Token::CloseBracket => {
                 tokens.next();
                 break;
             }
-            Token::Comma => {
+            Token::Comma if !elements.is_empty() && !after_comma => {
                 tokens.next();
-                continue;
+                after_comma = true;
+            }
+            _ if !elements.is_empty() && !after_comma => {
+                tokens.next();
+                return Err(ParseError::UnexpectedToken(token.clone()));
+            }
+            _ => {
+                elements.push(parse(tokens, options, depth + 1, stats)?);
+                after_comma = false;
             }
-            _ => elements.push(parse(tokens)),
         }
     }
 
-    JsonObject::Array(elements)
+    stats.max_width = stats.max_width.max(elements.len());
+    Ok(JsonObject::Array(elements))
 }
 
-fn parse_object(tokens: &mut Peekable<Iter<Token>>) -> JsonObject {
+fn parse_object(
+    tokens: &mut Peekable<Iter<Token>>,
+    options: &ParseOptions,
+    depth: usize,
+    stats: &mut ParseStats,
+) -> Result<JsonObject, ParseError> {
     let mut elements = HashMap::new();
+    let mut width = 0;
+    let mut started = false;
+    let mut after_comma = false;
 
-    while let Some(token) = tokens.peek() {
-        match token {
-            Token::CloseBrace => {
-                tokens.next();
-                break;
+    loop {
+        let token = tokens.next().ok_or(ParseError::UnexpectedEndOfInput)?;
+        let key = match token {
+            Token::CloseBrace if after_comma && !options.trailing_commas => {
+                return Err(ParseError::TrailingComma);
             }
-            Token::Comma => {
-                tokens.next();
+            Token::CloseBrace => break,
+            Token::Comma if started && !after_comma => {
+                after_comma = true;
                 continue;
             }
-            Token::String(key) => {
-                tokens.next();
-                elements.insert(key.to_string(), parse_object_entry(tokens));
+            _ if started && !after_comma => {
+                return Err(ParseError::UnexpectedToken(token.clone()));
             }
-            Token::Number(value) => {
-                tokens.next();
-                elements.insert(value.to_string(), parse_object_entry(tokens));
+            Token::String(key) => key.to_string(),
+            _ if options.strict || !options.json5 => {
+                return Err(ParseError::UnexpectedToken(token.clone()));
             }
-            Token::Boolean(value) => {
-                tokens.next();
-                elements.insert(value.to_string(), parse_object_entry(tokens));
-            }
-            _ => panic!("Invalid token inside object"),
+            Token::Integer(value) => value.to_string(),
+            Token::WideInteger(value) => value.to_string(),
+            Token::Number(value) => value.to_string(),
+            Token::Boolean(value) => value.to_string(),
+            _ => return Err(ParseError::UnexpectedToken(token.clone())),
+        };
+        if options.duplicate_keys.rejects_at(depth) && elements.contains_key(&key) {
+            return Err(ParseError::DuplicateKey(key));
         }
+
+        let value = parse_object_entry(tokens, options, depth, stats)?;
+        width += 1;
+        started = true;
+        after_comma = false;
+        elements.insert(key, value);
     }
 
-    JsonObject::Object(elements)
+    stats.max_width = stats.max_width.max(width);
+    Ok(JsonObject::Object(elements))
 }
 
-fn parse_object_entry(tokens: &mut Peekable<Iter<Token>>) -> JsonObject {
+fn parse_object_entry(
+    tokens: &mut Peekable<Iter<Token>>,
+    options: &ParseOptions,
+    depth: usize,
+    stats: &mut ParseStats,
+) -> Result<JsonObject, ParseError> {
     match tokens.next() {
-        Some(Token::Colon) => {
-            let value = parse(tokens);
-            value
-        }
-        _ => panic!("Expected colon after key"),
+        Some(Token::Colon) => parse(tokens, options, depth + 1, stats),
+        _ => Err(ParseError::ExpectedColon),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parser::options::DuplicateKeys;
 
     #[test]
-    fn parses_empty_tokens_as_null() {
+    fn rejects_empty_tokens() {
         let tokens = vec![];
-        let json = parse_tokens(tokens);
-        assert_eq!(json, JsonObject::Null);
+        assert_eq!(parse_tokens(tokens), Err(ParseError::UnexpectedEndOfInput));
+        assert_eq!(from_str(""), Err(ParseError::UnexpectedEndOfInput));
+        assert_eq!(from_str(" \n"), Err(ParseError::UnexpectedEndOfInput));
+    }
+
+    #[test]
+    fn parses_from_str() {
+        let json = from_str("[true, null]").unwrap();
+        assert_eq!(
+            json,
+            JsonObject::Array(vec![JsonObject::Boolean(true), JsonObject::Null])
+        );
+
+        assert!(matches!(from_str("a"), Err(ParseError::InvalidToken)));
+    }
+
+    #[test]
+    fn reports_max_width() {
+        let input = r#"{"a": [1, 2, 3, 4, 5], "b": {"c": [[], [6]], "d": null}, "e": "x"}"#;
+        let (json, stats) = from_str_with_stats(input, &ParseOptions::default()).unwrap();
+
+        assert_eq!(json, from_str(input).unwrap());
+        assert_eq!(stats, ParseStats { max_width: 5 });
+
+        let (_, stats) = from_str_with_stats(r#"{"k": 1, "k": 2}"#, &Default::default()).unwrap();
+        assert_eq!(stats.max_width, 2);
+        let (_, stats) = from_str_with_stats("7", &Default::default()).unwrap();
+        assert_eq!(stats.max_width, 0);
+    }
+
+    #[test]
+    fn requires_string_keys_unless_json5() {
+        let json5 = ParseOptions {
+            json5: true,
+            ..Default::default()
+        };
+        let strict = ParseOptions {
+            strict: true,
+            ..json5.clone()
+        };
+        assert_eq!(
+            from_str("{1: 2}"),
+            Err(ParseError::UnexpectedToken(Token::Integer(1)))
+        );
+        assert_eq!(
+            from_str("{true: 1}"),
+            Err(ParseError::UnexpectedToken(Token::Boolean(true)))
+        );
+        assert_eq!(
+            from_str_with_options("{1: 2}", &json5).unwrap()["1"],
+            JsonObject::Integer(2)
+        );
+        assert_eq!(
+            from_str_with_options("{1: 2}", &strict),
+            Err(ParseError::UnexpectedToken(Token::Integer(1)))
+        );
+    }
+
+    #[test]
+    fn reports_error_positions() {
+        let at = |error, line, column| {
+            Err(PositionedError {
+                error,
+                position: token::Position { line, column },
+            })
+        };
+        let parse = |input| from_str_with_position(input, &ParseOptions::default());
+
+        assert_eq!(
+            parse("{\n  \"a\": 1,\n  \"b\" 2\n}"),
+            at(ParseError::ExpectedColon, 3, 7)
+        );
+        assert_eq!(
+            parse("[\n  1,\n  true false]"),
+            at(ParseError::UnexpectedToken(Token::Boolean(false)), 3, 8)
+        );
+        assert_eq!(parse("[1]\n\n 2"), at(ParseError::TrailingTokens, 3, 2));
+        assert_eq!(parse("[\n1,"), at(ParseError::UnexpectedEndOfInput, 2, 2));
+        assert_eq!(parse("[\n1,\n@]"), at(ParseError::InvalidToken, 3, 1));
+        assert_eq!(parse(" [1, 2]"), Ok(from_str("[1, 2]").unwrap()));
+
+        let options = ParseOptions {
+            json5: true,
+            duplicate_keys: DuplicateKeys::Reject,
+            ..Default::default()
+        };
+        assert_eq!(
+            from_str_with_position("{\"a\": +1,\n \"a\": 2}", &options),
+            at(ParseError::DuplicateKey("a".to_string()), 2, 2)
+        );
+    }
+
+    #[test]
+    fn limits_nesting_depth() {
+        let input = "[".repeat(10_000) + &"]".repeat(10_000);
+        assert_eq!(from_str(&input), Err(ParseError::DepthLimitExceeded));
+        assert_eq!(
+            from_str(&"{\"a\": ".repeat(200)),
+            Err(ParseError::DepthLimitExceeded)
+        );
+
+        let options = ParseOptions {
+            max_depth: 2,
+            ..Default::default()
+        };
+        assert!(from_str_with_options("[{}, [1]]", &options).is_ok());
+        assert_eq!(
+            from_str_with_options("[[[]]]", &options),
+            Err(ParseError::DepthLimitExceeded)
+        );
+        assert!(from_str(&("[".repeat(128) + &"]".repeat(128))).is_ok());
+    }
+
+    #[test]
+    fn rejects_trailing_tokens() {
+        assert_eq!(from_str("1 2"), Err(ParseError::TrailingTokens));
+        assert_eq!(from_str("{} []"), Err(ParseError::TrailingTokens));
+        assert_eq!(from_str("true false"), Err(ParseError::TrailingTokens));
+        assert_eq!(
+            parse_tokens(vec![Token::Null, Token::Comma]),
+            Err(ParseError::TrailingTokens)
+        );
+        assert_eq!(
+            ParseError::TrailingTokens.to_string(),
+            "unexpected content after the value"
+        );
+        assert_eq!(
+            from_str(" [1] \n").unwrap(),
+            JsonObject::array_of([JsonObject::Integer(1)])
+        );
+    }
+
+    #[test]
+    fn rejects_trailing_commas() {
+        assert_eq!(from_str("[1,]"), Err(ParseError::TrailingComma));
+        assert_eq!(from_str(r#"{"a":1,}"#), Err(ParseError::TrailingComma));
+        assert_eq!(
+            from_str(r#"{"a": [[], {},]}"#),
+            Err(ParseError::TrailingComma)
+        );
+
+        assert_eq!(
+            from_str("[1,2]"),
+            Ok(JsonObject::Array(vec![
+                JsonObject::Number(1.0),
+                JsonObject::Number(2.0)
+            ]))
+        );
+        assert_eq!(
+            from_str(r#"{"a":1,"b":[]}"#),
+            Ok(JsonObject::object_of([
+                ("a".to_string(), JsonObject::Number(1.0)),
+                ("b".to_string(), JsonObject::Array(vec![])),
+            ]))
+        );
+    }
+
+    #[test]
+    fn parses_lenient_config_documents() {
+        let lenient = ParseOptions {
+            comments: true,
+            trailing_commas: true,
+            ..Default::default()
+        };
+        let input = r#"{
+            // Where to listen.
+            "port": 8080,
+            "hosts": ["a", "b",], /* both */
+        }"#;
+
+        assert_eq!(
+            from_str_with_options(input, &lenient),
+            from_str(r#"{"port": 8080, "hosts": ["a", "b"]}"#)
+        );
+        assert_eq!(from_str(input), Err(ParseError::InvalidToken));
+        assert_eq!(
+            from_str_with_options("[1,,]", &lenient),
+            Err(ParseError::UnexpectedToken(Token::Comma))
+        );
+    }
+
+    #[test]
+    fn rejects_misplaced_commas() {
+        for (input, token) in [
+            ("[,1]", Token::Comma),
+            ("[1,,2]", Token::Comma),
+            ("[1 2]", Token::Integer(2)),
+            (r#"{,"a":1}"#, Token::Comma),
+            (r#"{"a":1 "b":2}"#, Token::String("b".into())),
+        ] {
+            assert_eq!(
+                from_str(input),
+                Err(ParseError::UnexpectedToken(token)),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_tokens() {
+        let tokens = vec![
+            Token::OpenBrace,
+            Token::String("foo".into()),
+            Token::Number(1.0),
+            Token::CloseBrace,
+        ];
+        assert_eq!(parse_tokens(tokens), Err(ParseError::ExpectedColon));
+
+        let tokens = vec![Token::OpenBrace, Token::Null, Token::CloseBrace];
+        assert_eq!(
+            parse_tokens(tokens),
+            Err(ParseError::UnexpectedToken(Token::Null))
+        );
+
+        let tokens = vec![Token::OpenBracket, Token::Colon, Token::CloseBracket];
+        assert_eq!(
+            parse_tokens(tokens),
+            Err(ParseError::UnexpectedToken(Token::Colon))
+        );
+
+        let tokens = vec![Token::OpenBrace, Token::String("foo".into()), Token::Colon];
+        assert_eq!(parse_tokens(tokens), Err(ParseError::UnexpectedEndOfInput));
+
+        assert_eq!(from_str("[1, [2]"), Err(ParseError::UnexpectedEndOfInput));
+        assert_eq!(
+            from_str(r#"{"a": 1"#),
+            Err(ParseError::UnexpectedEndOfInput)
+        );
+    }
+
+    #[test]
+    fn rejects_stray_closing_delimiters() {
+        assert_eq!(
+            from_str("}"),
+            Err(ParseError::UnexpectedToken(Token::CloseBrace))
+        );
+        assert_eq!(
+            from_str("]"),
+            Err(ParseError::UnexpectedToken(Token::CloseBracket))
+        );
+        assert_eq!(
+            parse_tokens_with_options(vec![Token::Colon], &ParseOptions::default()),
+            Err(ParseError::UnexpectedToken(Token::Colon))
+        );
+    }
+
+    #[test]
+    fn shares_parsed_documents_across_threads() {
+        let json = parse_shared(r#"{"workers": [1, 2, 3, 4], "name": "pool"}"#).unwrap();
+
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let json = Arc::clone(&json);
+                std::thread::spawn(move || {
+                    let JsonObject::Object(map) = &*json else {
+                        panic!("Expected object");
+                    };
+                    let JsonObject::Array(workers) = &map["workers"] else {
+                        panic!("Expected array");
+                    };
+                    workers[i].clone()
+                })
+            })
+            .collect();
+
+        let workers: Vec<_> = handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect();
+        assert_eq!(
+            JsonObject::Array(workers),
+            from_str("[1, 2, 3, 4]").unwrap()
+        );
+        assert_eq!(
+            json.count_matching(|node| matches!(node, JsonObject::Integer(_))),
+            4
+        );
+    }
+
+    #[test]
+    fn compares_documents_structurally() {
+        let compact = r#"{"a":[1,2,{"b":null}],"c":"x"}"#;
+        let pretty =
+            "{\n  \"c\": \"x\",\n  \"a\": [\n    1,\n    2.0,\n    { \"b\": null }\n  ]\n}";
+
+        assert_eq!(json_eq(compact, pretty), Ok(true));
+        assert_eq!(
+            json_eq(compact, r#"{"a":[2,1,{"b":null}],"c":"x"}"#),
+            Ok(false)
+        );
+        assert_eq!(json_eq(compact, "a"), Err(ParseError::InvalidToken));
+    }
+
+    #[test]
+    fn from_str_round_trips_simple_documents() {
+        use crate::json::serialize::to_string;
+
+        for input in [
+            r#"{"a":[1,2,3]}"#,
+            r#"[true,false,null]"#,
+            r#"{"nested":{"key":"value"}}"#,
+            r#""text""#,
+            "[]",
+            "{}",
+            "-2.5",
+        ] {
+            assert_eq!(to_string(&from_str(input).unwrap()), input);
+        }
+
+        let json = from_str(" { \"a\" : [ 1 , 2 ] } ").unwrap();
+        assert_eq!(to_string(&json), r#"{"a":[1,2]}"#);
+        assert_eq!(from_str("[1, 2"), Err(ParseError::UnexpectedEndOfInput));
+    }
+
+    #[test]
+    fn preserves_integer_precision() {
+        use crate::{
+            json::serialize::to_string,
+            message_pack::{deserialize::deserialize, serialize::try_serialize},
+        };
+
+        let input = "[9007199254740993,-9223372036854775808,9223372036854775807,1.5]";
+        let json = from_str(input).unwrap();
+
+        let JsonObject::Array(elements) = &json else {
+            panic!("expected an array");
+        };
+        assert!(matches!(elements[0], JsonObject::Integer(9007199254740993)));
+        assert!(matches!(elements[3], JsonObject::Number(_)));
+        assert_eq!(to_string(&json), input);
+
+        let decoded = deserialize(&try_serialize(&json).unwrap()).unwrap();
+        assert_eq!(to_string(&decoded), input);
+    }
+
+    #[test]
+    fn compares_numbers_across_variants() {
+        assert_eq!(JsonObject::Integer(1), JsonObject::Number(1.0));
+        assert_eq!(JsonObject::Number(-0.0), JsonObject::Integer(0));
+        assert_ne!(JsonObject::Integer(1), JsonObject::Number(1.5));
+        assert_ne!(
+            JsonObject::Integer(9007199254740993),
+            JsonObject::Number(9007199254740992.0)
+        );
+        assert_eq!(
+            JsonObject::WideInteger(1 << 100),
+            JsonObject::Number(2f64.powi(100))
+        );
+        assert_ne!(
+            JsonObject::Integer(i64::MAX),
+            JsonObject::Number(2f64.powi(63))
+        );
+        assert_ne!(JsonObject::Integer(0), JsonObject::Number(f64::NAN));
+    }
+
+    #[test]
+    fn wide_integers_round_trip_losslessly() {
+        use crate::json::serialize::to_string;
+
+        let options = ParseOptions {
+            wide_integers: true,
+            ..Default::default()
+        };
+        let input = "[123456789012345678901234567890,-9223372036854775809,7]";
+
+        let json = from_str_with_options(input, &options).unwrap();
+        assert_eq!(
+            json,
+            JsonObject::Array(vec![
+                JsonObject::WideInteger(123456789012345678901234567890),
+                JsonObject::WideInteger(-9223372036854775809),
+                JsonObject::Number(7.0),
+            ])
+        );
+        assert_eq!(to_string(&json), input);
+
+        let json = from_str(input).unwrap();
+        assert_eq!(
+            to_string(&json),
+            "[123456789012345680000000000000,-9223372036854776000,7]"
+        );
+    }
+
+    #[test]
+    fn parses_from_reader_with_limit() {
+        let input = br#"{"a": [1, 2, 3]}"#;
+
+        let json = JsonObject::try_from_reader(&input[..], input.len()).unwrap();
+        assert_eq!(json, from_str(r#"{"a": [1, 2, 3]}"#).unwrap());
+
+        assert_eq!(
+            JsonObject::try_from_reader(&input[..], input.len() - 1),
+            Err(ParseError::InputTooLarge)
+        );
+        assert_eq!(
+            JsonObject::try_from_reader(std::io::repeat(b' '), 1024),
+            Err(ParseError::InputTooLarge)
+        );
+        assert_eq!(
+            JsonObject::try_from_reader(&b"\"\xff\""[..], 16),
+            Err(ParseError::InvalidUtf8)
+        );
     }
 
     #[test]
     fn parses_literals() {
         let tokens = vec![Token::Null];
-        let json = parse_tokens(tokens);
+        let json = parse_tokens(tokens).unwrap();
         assert_eq!(json, JsonObject::Null);
 
         let tokens = vec![Token::Boolean(true)];
-        let json = parse_tokens(tokens);
+        let json = parse_tokens(tokens).unwrap();
         assert_eq!(json, JsonObject::Boolean(true));
 
         let tokens = vec![Token::Number(42.69)];
-        let json = parse_tokens(tokens);
+        let json = parse_tokens(tokens).unwrap();
         assert_eq!(json, JsonObject::Number(42.69));
 
-        let tokens = vec![Token::String("Foo".to_string())];
-        let json = parse(&mut tokens.iter().peekable());
+        let tokens = [Token::String("Foo".into())];
+        let json = parse(
+            &mut tokens.iter().peekable(),
+            &ParseOptions::default(),
+            0,
+            &mut ParseStats::default(),
+        )
+        .unwrap();
         assert_eq!(json, JsonObject::String("Foo".to_string()));
     }
 
     #[test]
     fn parses_arrays() {
         let tokens = vec![Token::OpenBracket, Token::CloseBracket];
-        let json = parse_tokens(tokens);
+        let json = parse_tokens(tokens).unwrap();
         assert_eq!(json, JsonObject::Array(vec![]));
 
         let tokens = vec![
@@ -138,7 +766,7 @@ mod tests {
             Token::Number(42.69),
             Token::CloseBracket,
         ];
-        let json = parse_tokens(tokens);
+        let json = parse_tokens(tokens).unwrap();
         assert_eq!(json, JsonObject::Array(vec![JsonObject::Number(42.69)]));
 
         let tokens = vec![
@@ -148,7 +776,7 @@ mod tests {
             Token::Number(69.42),
             Token::CloseBracket,
         ];
-        let json = parse_tokens(tokens);
+        let json = parse_tokens(tokens).unwrap();
         assert_eq!(
             json,
             JsonObject::Array(vec![JsonObject::Number(42.69), JsonObject::Number(69.42)])
@@ -161,7 +789,7 @@ mod tests {
             Token::CloseBracket,
             Token::CloseBracket,
         ];
-        let json = parse_tokens(tokens);
+        let json = parse_tokens(tokens).unwrap();
         assert_eq!(
             json,
             JsonObject::Array(vec![JsonObject::Array(vec![JsonObject::Number(42.69)])])
@@ -171,28 +799,28 @@ mod tests {
     #[test]
     fn test_objects() {
         let tokens = vec![Token::OpenBrace, Token::CloseBrace];
-        let json = parse_tokens(tokens);
+        let json = parse_tokens(tokens).unwrap();
         assert_eq!(json, JsonObject::Object(HashMap::new()));
 
         let tokens = vec![
             Token::OpenBrace,
-            Token::String("foo".to_string()),
+            Token::String("foo".into()),
             Token::Colon,
             Token::Number(42.69),
             Token::CloseBrace,
         ];
         let mut map = HashMap::new();
         map.insert("foo".to_string(), JsonObject::Number(42.69));
-        let json = parse_tokens(tokens);
+        let json = parse_tokens(tokens).unwrap();
         assert_eq!(json, JsonObject::Object(map));
 
         let tokens = vec![
             Token::OpenBrace,
-            Token::String("foo".to_string()),
+            Token::String("foo".into()),
             Token::Colon,
             Token::Number(42.69),
             Token::Comma,
-            Token::String("bar".to_string()),
+            Token::String("bar".into()),
             Token::Colon,
             Token::Number(69.42),
             Token::CloseBrace,
@@ -200,12 +828,12 @@ mod tests {
         let mut map = HashMap::new();
         map.insert("foo".to_string(), JsonObject::Number(42.69));
         map.insert("bar".to_string(), JsonObject::Number(69.42));
-        let json = parse_tokens(tokens);
+        let json = parse_tokens(tokens).unwrap();
         assert_eq!(json, JsonObject::Object(map));
 
         let tokens = vec![
             Token::OpenBrace,
-            Token::String("foo".to_string()),
+            Token::String("foo".into()),
             Token::Colon,
             Token::OpenBrace,
             Token::Boolean(false),
@@ -218,7 +846,92 @@ mod tests {
         let mut inner_map = HashMap::new();
         inner_map.insert("false".to_string(), JsonObject::Number(42.69));
         map.insert("foo".to_string(), JsonObject::Object(inner_map));
-        let json = parse_tokens(tokens);
+        let json5 = ParseOptions {
+            json5: true,
+            ..Default::default()
+        };
+        let json = parse_tokens_with_options(tokens.clone(), &json5).unwrap();
         assert_eq!(json, JsonObject::Object(map));
+        assert_eq!(
+            parse_tokens(tokens),
+            Err(ParseError::UnexpectedToken(Token::Boolean(false)))
+        );
+    }
+
+    #[test]
+    fn rejects_duplicate_keys_only_when_asked() {
+        let input = r#"{"a":1,"a":2}"#;
+        let reject = ParseOptions {
+            duplicate_keys: DuplicateKeys::Reject,
+            ..Default::default()
+        };
+
+        let error = from_str_with_options(input, &reject).unwrap_err();
+        assert_eq!(error, ParseError::DuplicateKey("a".to_string()));
+        assert_eq!(error.to_string(), "duplicate key `a`");
+        assert_eq!(from_str(input).unwrap()["a"], JsonObject::Integer(2));
+        assert!(from_str_with_options(r#"{"a":1,"b":{"a":2}}"#, &reject).is_ok());
+    }
+
+    #[test]
+    fn rejects_duplicate_keys_by_depth() {
+        let input = r#"{"a": 1, "a": 2, "inner": {"b": 1, "b": 2}}"#;
+
+        let json = from_str(input).unwrap();
+        let JsonObject::Object(map) = json else {
+            panic!("Expected object");
+        };
+        assert_eq!(map.get("a"), Some(&JsonObject::Number(2.0)));
+
+        let reject = ParseOptions {
+            duplicate_keys: DuplicateKeys::Reject,
+            ..Default::default()
+        };
+        assert_eq!(
+            from_str_with_options(r#"{"inner": {"b": 1, "b": 2}}"#, &reject),
+            Err(ParseError::DuplicateKey("b".to_string()))
+        );
+
+        let root_only = ParseOptions {
+            duplicate_keys: DuplicateKeys::RejectUpToDepth(0),
+            ..Default::default()
+        };
+        assert_eq!(
+            from_str_with_options(input, &root_only),
+            Err(ParseError::DuplicateKey("a".to_string()))
+        );
+
+        let json = from_str_with_options(r#"{"a": 1, "inner": {"b": 1, "b": 2}}"#, &root_only);
+        let mut inner = HashMap::new();
+        inner.insert("b".to_string(), JsonObject::Number(2.0));
+        let mut map = HashMap::new();
+        map.insert("a".to_string(), JsonObject::Number(1.0));
+        map.insert("inner".to_string(), JsonObject::Object(inner));
+        assert_eq!(json, Ok(JsonObject::Object(map)));
+    }
+
+    #[test]
+    fn object_equality_ignores_key_order() {
+        let forward = JsonObject::object_of([
+            ("a".to_string(), JsonObject::Number(1.0)),
+            ("b".to_string(), JsonObject::Null),
+            ("c".to_string(), JsonObject::Boolean(true)),
+        ]);
+        let backward = JsonObject::object_of([
+            ("c".to_string(), JsonObject::Boolean(true)),
+            ("b".to_string(), JsonObject::Null),
+            ("a".to_string(), JsonObject::Number(1.0)),
+        ]);
+        assert_eq!(forward, backward);
+
+        assert_eq!(
+            from_str(r#"{"x": {"a": 1, "b": [1, 2]}, "y": null}"#).unwrap(),
+            from_str(r#"{"y": null, "x": {"b": [1, 2], "a": 1}}"#).unwrap()
+        );
+        assert_ne!(
+            from_str(r#"{"a": 1, "b": 2}"#).unwrap(),
+            from_str(r#"{"a": 2, "b": 1}"#).unwrap()
+        );
+        assert_ne!(from_str("[1, 2]").unwrap(), from_str("[2, 1]").unwrap());
     }
 }
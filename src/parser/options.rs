@@ -0,0 +1,75 @@
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Maximum length of a single string literal, in bytes.
+    pub max_string_len: Option<usize>,
+    /// Record number literals that are parsed as `f64` and cannot be
+    /// represented exactly. Integers kept as `Integer` or `WideInteger` are
+    /// exact and never reported. See `Tokenizer::precision_loss`.
+    pub warn_on_precision_loss: bool,
+    /// Accept JSON5 number syntax: a leading `+`, hexadecimal literals,
+    /// leading or trailing decimal points, `Infinity` and `NaN`. Numbers and
+    /// booleans are also accepted as object keys and converted to strings.
+    pub json5: bool,
+    /// Decode unpaired surrogates in `\u` escapes as U+FFFD instead of
+    /// failing with `ParseError::InvalidUnicodeEscape`.
+    pub lenient_surrogates: bool,
+    /// Keep integer literals outside the `i64` range as `i128` instead of
+    /// rounding them to `f64`. Literals outside the `i128` range are still
+    /// parsed as `f64`.
+    pub wide_integers: bool,
+    /// Skip `//` line comments and `/* */` block comments between tokens.
+    pub comments: bool,
+    /// Accept a comma before the `]` or `}` closing an array or object.
+    pub trailing_commas: bool,
+    pub duplicate_keys: DuplicateKeys,
+    /// Follow RFC 8259 to the letter: reject object keys that are not
+    /// strings even when `json5` is set.
+    pub strict: bool,
+    /// How many arrays and objects may be nested inside each other before
+    /// parsing fails with `ParseError::DepthLimitExceeded`. Defaults to 128.
+    pub max_depth: usize,
+    /// Share one allocation between string tokens with the same short value,
+    /// such as field values repeated across records. Strings are decoded into
+    /// a buffer kept between tokens, so a repeated value allocates nothing
+    /// and any other value is allocated once at its final length.
+    pub intern_strings: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            max_string_len: None,
+            warn_on_precision_loss: false,
+            json5: false,
+            lenient_surrogates: false,
+            wide_integers: false,
+            comments: false,
+            trailing_commas: false,
+            duplicate_keys: DuplicateKeys::default(),
+            strict: false,
+            max_depth: 128,
+            intern_strings: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum DuplicateKeys {
+    /// A repeated key replaces the earlier value.
+    #[default]
+    LastWins,
+    Reject,
+    /// Reject repeated keys only in objects nested at most this many levels
+    /// below the root, where the root value is at depth 0.
+    RejectUpToDepth(usize),
+}
+
+impl DuplicateKeys {
+    pub fn rejects_at(&self, depth: usize) -> bool {
+        match self {
+            DuplicateKeys::LastWins => false,
+            DuplicateKeys::Reject => true,
+            DuplicateKeys::RejectUpToDepth(max_depth) => depth <= *max_depth,
+        }
+    }
+}
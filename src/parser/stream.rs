@@ -0,0 +1,82 @@
+use std::ops::Range;
+
+use super::{
+    options::ParseOptions,
+    token::{ParseError, Token, Tokenizer},
+};
+
+/// Finds the byte range of each top-level value in a stream of concatenated
+/// JSON values, such as `{"a": 1} [2] "three"`.
+///
+/// Values are delimited by matching brackets and are not parsed, so the
+/// contents of a container are only checked for valid tokens.
+pub fn index_stream(input: &str) -> Result<Vec<Range<usize>>, ParseError> {
+    let mut tokens = Tokenizer::new(input.chars(), ParseOptions::default());
+    let mut ranges = vec![];
+    let mut closers = vec![];
+    let mut start = 0;
+
+    while let Some(token) = tokens.next_spanned() {
+        let (token, span) = token?;
+        if closers.is_empty() {
+            start = span.start;
+        }
+
+        match token {
+            Token::OpenBrace => closers.push(Token::CloseBrace),
+            Token::OpenBracket => closers.push(Token::CloseBracket),
+            Token::CloseBrace | Token::CloseBracket if closers.pop() != Some(token.clone()) => {
+                return Err(ParseError::UnexpectedToken(token));
+            }
+            Token::Colon | Token::Comma if closers.is_empty() => {
+                return Err(ParseError::UnexpectedToken(token));
+            }
+            _ => {}
+        }
+
+        if closers.is_empty() {
+            ranges.push(start..span.end);
+        }
+    }
+
+    if !closers.is_empty() {
+        return Err(ParseError::UnexpectedEndOfInput);
+    }
+
+    Ok(ranges)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{from_str, JsonObject};
+
+    #[test]
+    fn indexes_top_level_values() {
+        let input = "{\"a\": [1, {\"b\": 2}]}\n-4.5 \"caf\u{e9}\"[]";
+        let ranges = index_stream(input).unwrap();
+
+        assert_eq!(ranges, [0..20, 21..25, 26..33, 33..35]);
+        assert_eq!(
+            from_str(&input[ranges[2].clone()]).unwrap(),
+            JsonObject::String("caf\u{e9}".to_string())
+        );
+        assert_eq!(index_stream(" \n").unwrap(), []);
+    }
+
+    #[test]
+    fn rejects_unbalanced_streams() {
+        assert_eq!(
+            index_stream("[1, 2} [3]"),
+            Err(ParseError::UnexpectedToken(Token::CloseBrace))
+        );
+        assert_eq!(
+            index_stream("1, 2"),
+            Err(ParseError::UnexpectedToken(Token::Comma))
+        );
+        assert_eq!(
+            index_stream("{} {\"a\": [1]"),
+            Err(ParseError::UnexpectedEndOfInput)
+        );
+    }
+}
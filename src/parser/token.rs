@@ -1,6 +1,11 @@
-use std::{iter::Peekable, num::ParseFloatError, str::Chars};
+use std::{
+    collections::HashSet, fmt, io, iter::Peekable, num::ParseFloatError, ops::Range, sync::Arc,
+};
 
-#[derive(Debug, PartialEq)]
+use super::options::ParseOptions;
+use crate::json::serialize::escape_json_string;
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Token {
     OpenBrace,
     CloseBrace,
@@ -8,83 +13,665 @@ pub enum Token {
     CloseBracket,
     Colon,
     Comma,
-    String(String),
+    /// Shared so that repeated values can point at one allocation with
+    /// `ParseOptions::intern_strings`.
+    String(Arc<str>),
+    /// A number literal without a decimal point or exponent that fits in an
+    /// `i64`. Other number literals are tokenized as `Number`.
+    Integer(i64),
+    /// An integer literal outside the `i64` range, only produced with
+    /// `ParseOptions::wide_integers`.
+    WideInteger(i128),
     Number(f64),
     Boolean(bool),
     Null,
 }
 
+impl Token {
+    /// Renders the token back into JSON text. `Number` tokens keep a decimal
+    /// point or exponent, so `1.0` is not turned into `1`. Non-finite numbers
+    /// use their JSON5 spellings.
+    pub fn to_source(&self) -> String {
+        match self {
+            Token::OpenBrace => "{".to_string(),
+            Token::CloseBrace => "}".to_string(),
+            Token::OpenBracket => "[".to_string(),
+            Token::CloseBracket => "]".to_string(),
+            Token::Colon => ":".to_string(),
+            Token::Comma => ",".to_string(),
+            Token::String(value) => format!("\"{}\"", escape_json_string(value)),
+            Token::Number(value) if value.is_nan() => "NaN".to_string(),
+            Token::Number(value) if value.is_infinite() && *value > 0.0 => "Infinity".to_string(),
+            Token::Number(value) if value.is_infinite() => "-Infinity".to_string(),
+            Token::Integer(value) => value.to_string(),
+            Token::WideInteger(value) => value.to_string(),
+            Token::Number(value) => format!("{:?}", value),
+            Token::Boolean(value) => value.to_string(),
+            Token::Null => "null".to_string(),
+        }
+    }
+
+    /// Compares tokens like `==`, except that numbers (including integers)
+    /// are equal when they differ by at most `epsilon`.
+    pub fn approx_eq(&self, other: &Token, epsilon: f64) -> bool {
+        match (self.as_f64(), other.as_f64()) {
+            (Some(a), Some(b)) => a == b || (a - b).abs() <= epsilon,
+            _ => self == other,
+        }
+    }
+
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Token::Integer(value) => Some(*value as f64),
+            Token::WideInteger(value) => Some(*value as f64),
+            Token::Number(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum ParseError {
     InvalidNumber(ParseFloatError),
+    /// A number literal that Rust can parse but the JSON grammar does not
+    /// allow, such as `012` or `1.`.
+    MalformedNumber(String),
     UnexpectedEndOfInput,
     InvalidToken,
+    /// A literal such as `true` was cut short by an unexpected character.
+    InvalidKeyword {
+        expected: &'static str,
+        found: String,
+    },
+    StringTooLong,
+    /// A character below U+0020 appears inside a string without being
+    /// escaped.
+    UnescapedControlCharacter(char),
+    /// A `\u` escape has invalid hex digits or is an unpaired surrogate.
+    InvalidUnicodeEscape,
+    UnexpectedToken(Token),
+    ExpectedColon,
+    /// A comma is directly followed by the `]` or `}` closing its container.
+    TrailingComma,
+    DuplicateKey(String),
+    /// Arrays and objects are nested deeper than `ParseOptions::max_depth`.
+    DepthLimitExceeded,
+    /// More tokens follow a complete top-level value.
+    TrailingTokens,
+    /// The input is longer than the configured byte limit.
+    InputTooLarge,
+    InvalidUtf8,
+    Io(io::ErrorKind),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidNumber(error) => write!(f, "invalid number: {}", error),
+            ParseError::MalformedNumber(literal) => write!(f, "malformed number `{}`", literal),
+            ParseError::UnexpectedEndOfInput => write!(f, "unexpected end of input"),
+            ParseError::InvalidToken => write!(f, "invalid token"),
+            ParseError::InvalidKeyword { expected, found } => {
+                write!(f, "expected `{}`, found `{}`", expected, found)
+            }
+            ParseError::StringTooLong => write!(f, "string exceeds the maximum length"),
+            ParseError::UnescapedControlCharacter(char) => {
+                write!(
+                    f,
+                    "unescaped control character U+{:04X} in string",
+                    *char as u32
+                )
+            }
+            ParseError::InvalidUnicodeEscape => write!(f, "invalid unicode escape"),
+            ParseError::UnexpectedToken(token) => {
+                write!(f, "unexpected token `{}`", token.to_source())
+            }
+            ParseError::ExpectedColon => write!(f, "expected `:` after object key"),
+            ParseError::TrailingComma => write!(f, "trailing comma before closing delimiter"),
+            ParseError::DuplicateKey(key) => write!(f, "duplicate key `{}`", key),
+            ParseError::DepthLimitExceeded => write!(f, "nesting exceeds the maximum depth"),
+            ParseError::TrailingTokens => write!(f, "unexpected content after the value"),
+            ParseError::InputTooLarge => write!(f, "input exceeds the maximum size"),
+            ParseError::InvalidUtf8 => write!(f, "input is not valid UTF-8"),
+            ParseError::Io(kind) => write!(f, "failed to read input: {}", kind),
+        }
+    }
+}
+
+/// A line and column in the input, both starting at 1. Columns count
+/// characters, not bytes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A `ParseError` along with the position of the character at which it was
+/// detected.
+#[derive(Debug, PartialEq)]
+pub struct PositionedError {
+    pub error: ParseError,
+    pub position: Position,
+}
+
+impl fmt::Display for PositionedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {}",
+            self.error, self.position.line, self.position.column
+        )
+    }
 }
 
 pub fn tokenize(input: String) -> Result<Vec<Token>, ParseError> {
-    let mut input = input.chars().peekable();
+    tokenize_with_options(input, &ParseOptions::default())
+}
+
+pub fn tokenize_with_options(
+    input: String,
+    options: &ParseOptions,
+) -> Result<Vec<Token>, ParseError> {
+    Tokenizer::new(input.chars(), options.clone()).collect()
+}
+
+/// Like `tokenize`, but reports where in the input an error occurred.
+pub fn tokenize_with_position(input: String) -> Result<Vec<Token>, PositionedError> {
+    tokenize_with_position_and_options(input, &ParseOptions::default())
+}
+
+pub fn tokenize_with_position_and_options(
+    input: String,
+    options: &ParseOptions,
+) -> Result<Vec<Token>, PositionedError> {
+    tokenize_located(&input, options).map(|located| located.tokens)
+}
+
+/// Tokens along with where each of them starts.
+pub(crate) struct LocatedTokens {
+    pub tokens: Vec<Token>,
+    pub starts: Vec<Position>,
+    /// The position of the last character in the input.
+    pub end: Position,
+}
+
+pub(crate) fn tokenize_located(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<LocatedTokens, PositionedError> {
+    let mut tokenizer = Tokenizer::new(input.chars(), options.clone());
     let mut tokens = vec![];
+    let mut starts = vec![];
+
+    while let Some(token) = tokenizer.next() {
+        match token {
+            Ok(token) => {
+                tokens.push(token);
+                starts.push(tokenizer.token_position);
+            }
+            Err(error) => {
+                return Err(PositionedError {
+                    error,
+                    position: tokenizer.position(),
+                })
+            }
+        }
+    }
+
+    Ok(LocatedTokens {
+        tokens,
+        starts,
+        end: tokenizer.position(),
+    })
+}
+
+/// Produces tokens one at a time from any source of characters, so input can
+/// be tokenized without holding all of it in memory.
+pub struct Tokenizer<I: Iterator<Item = char>> {
+    input: Source<I>,
+    options: ParseOptions,
+    precision_loss: Vec<String>,
+    token_start: usize,
+    /// The position of the first character of the last token.
+    token_position: Position,
+    /// Scratch space for decoding strings with `ParseOptions::intern_strings`.
+    string_buffer: String,
+    /// Short string values seen so far with `ParseOptions::intern_strings`.
+    interned: HashSet<Arc<str>>,
+}
+
+/// Strings up to this many bytes are interned with
+/// `ParseOptions::intern_strings`. Longer values are rarely repeated exactly.
+const MAX_INTERNED_LEN: usize = 32;
+
+impl<I: Iterator<Item = char>> Tokenizer<I> {
+    pub fn new(input: I, options: ParseOptions) -> Self {
+        Tokenizer {
+            input: Source {
+                chars: input.peekable(),
+                offset: 0,
+                position: Position { line: 1, column: 0 },
+                after_newline: false,
+            },
+            options,
+            precision_loss: vec![],
+            token_start: 0,
+            token_position: Position { line: 1, column: 0 },
+            string_buffer: String::new(),
+            interned: HashSet::new(),
+        }
+    }
+
+    /// The position of the last character read. After an error, this is where
+    /// the error was detected.
+    pub fn position(&self) -> Position {
+        self.input.position
+    }
+
+    /// Like `next`, but also yields the byte range the token spans in the
+    /// input.
+    pub fn next_spanned(&mut self) -> Option<Result<(Token, Range<usize>), ParseError>> {
+        let token = self.next_token().transpose()?;
+        Some(token.map(|token| (token, self.token_start..self.input.offset)))
+    }
+
+    /// Number literals seen so far that lost precision when parsed, if
+    /// `ParseOptions::warn_on_precision_loss` is set.
+    pub fn precision_loss(&self) -> &[String] {
+        &self.precision_loss
+    }
 
-    while let Some(char) = input.next() {
-        let token = match char {
-            '{' => Token::OpenBrace,
-            '}' => Token::CloseBrace,
-            '[' => Token::OpenBracket,
-            ']' => Token::CloseBracket,
-            ':' => Token::Colon,
-            ',' => Token::Comma,
-            '"' => {
-                // TODO: catch undetermined strings
-                let mut string = String::new();
-                for char in input.by_ref() {
-                    match char {
-                        '"' => break,
-                        _ => string.push(char),
+    fn next_token(&mut self) -> Result<Option<Token>, ParseError> {
+        let json5 = self.options.json5;
+
+        while let Some(char) = self.input.next() {
+            self.token_start = self.input.offset - char.len_utf8();
+            self.token_position = self.input.position;
+            let token = match char {
+                '{' => Token::OpenBrace,
+                '}' => Token::CloseBrace,
+                '[' => Token::OpenBracket,
+                ']' => Token::CloseBracket,
+                ':' => Token::Colon,
+                ',' => Token::Comma,
+                '"' => self.read_string()?,
+                '0'..='9' | '-' => self.read_number(char)?,
+                '+' | '.' if json5 => self.read_number(char)?,
+                't' => assert_next_chars(&mut self.input, "true").map(|_| Token::Boolean(true))?,
+                'f' => {
+                    assert_next_chars(&mut self.input, "false").map(|_| Token::Boolean(false))?
+                }
+                'n' => assert_next_chars(&mut self.input, "null").map(|_| Token::Null)?,
+                'I' if json5 => assert_next_chars(&mut self.input, "Infinity")
+                    .map(|_| Token::Number(f64::INFINITY))?,
+                'N' if json5 => {
+                    assert_next_chars(&mut self.input, "NaN").map(|_| Token::Number(f64::NAN))?
+                }
+                ' ' | '\n' | '\r' | '\t' => continue,
+                '/' if self.options.comments => {
+                    self.skip_comment()?;
+                    continue;
+                }
+                _ => return Err(ParseError::InvalidToken),
+            };
+
+            return Ok(Some(token));
+        }
+
+        Ok(None)
+    }
+
+    /// Skips a comment whose leading `/` has already been consumed.
+    fn skip_comment(&mut self) -> Result<(), ParseError> {
+        match self.input.next() {
+            Some('/') => {
+                for char in self.input.by_ref() {
+                    if char == '\n' {
+                        break;
+                    }
+                }
+                Ok(())
+            }
+            Some('*') => {
+                let mut previous = None;
+                loop {
+                    let char = self.input.next().ok_or(ParseError::UnexpectedEndOfInput)?;
+                    if previous == Some('*') && char == '/' {
+                        return Ok(());
                     }
+                    previous = Some(char);
                 }
+            }
+            Some(_) => Err(ParseError::InvalidToken),
+            None => Err(ParseError::UnexpectedEndOfInput),
+        }
+    }
 
-                Token::String(string)
+    fn read_string(&mut self) -> Result<Token, ParseError> {
+        let mut string = String::new();
+        if self.options.intern_strings {
+            string = std::mem::take(&mut self.string_buffer);
+            string.clear();
+        }
+
+        loop {
+            match self.input.next().ok_or(ParseError::UnexpectedEndOfInput)? {
+                '"' => break,
+                '\\' => self.read_escape(&mut string)?,
+                char if char < ' ' => return Err(ParseError::UnescapedControlCharacter(char)),
+                char => self.push_char(&mut string, char)?,
             }
-            '0'..='9' => {
-                let mut number = String::new();
-                number.push(char);
-
-                while let Some(&char) = input.peek() {
-                    match char {
-                        '0'..='9' | '.' | 'e' | 'E' => {
-                            number.push(char);
-                            input.next();
+        }
+
+        if self.options.intern_strings {
+            let value = self.intern(&string);
+            self.string_buffer = string;
+            return Ok(Token::String(value));
+        }
+        Ok(Token::String(string.into()))
+    }
+
+    /// Returns the shared copy of `value` if it was seen before, and records
+    /// it for later tokens if it is short.
+    fn intern(&mut self, value: &str) -> Arc<str> {
+        if let Some(shared) = self.interned.get(value) {
+            return Arc::clone(shared);
+        }
+
+        let shared: Arc<str> = value.into();
+        if value.len() <= MAX_INTERNED_LEN {
+            self.interned.insert(Arc::clone(&shared));
+        }
+        shared
+    }
+
+    /// Decodes the escape sequence after a backslash into `string`.
+    fn read_escape(&mut self, string: &mut String) -> Result<(), ParseError> {
+        let char = match self.input.next() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => return self.read_unicode_escape(string),
+            Some(_) => return Err(ParseError::InvalidToken),
+            None => return Err(ParseError::UnexpectedEndOfInput),
+        };
+        self.push_char(string, char)
+    }
+
+    /// Decodes the code point after `\u` into `string`, combining a surrogate
+    /// pair written as two consecutive escapes.
+    fn read_unicode_escape(&mut self, string: &mut String) -> Result<(), ParseError> {
+        let mut code = self.read_hex4()?;
+
+        loop {
+            let char = match code {
+                high @ 0xd800..=0xdbff => {
+                    if self.input.peek() != Some(&'\\') {
+                        return self.push_unpaired_surrogate(string);
+                    }
+                    self.input.next();
+                    if self.input.peek() != Some(&'u') {
+                        self.push_unpaired_surrogate(string)?;
+                        return self.read_escape(string);
+                    }
+                    self.input.next();
+
+                    match self.read_hex4()? {
+                        low @ 0xdc00..=0xdfff => 0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00),
+                        next => {
+                            // Whatever follows the unpaired surrogate is decoded
+                            // in its own right, and may start a pair itself.
+                            self.push_unpaired_surrogate(string)?;
+                            code = next;
+                            continue;
                         }
-                        _ => break,
                     }
                 }
+                0xdc00..=0xdfff => return self.push_unpaired_surrogate(string),
+                code => code,
+            };
+
+            let char = char::from_u32(char).ok_or(ParseError::InvalidUnicodeEscape)?;
+            return self.push_char(string, char);
+        }
+    }
+
+    fn push_unpaired_surrogate(&mut self, string: &mut String) -> Result<(), ParseError> {
+        if self.options.lenient_surrogates {
+            self.push_char(string, char::REPLACEMENT_CHARACTER)
+        } else {
+            Err(ParseError::InvalidUnicodeEscape)
+        }
+    }
+
+    fn push_char(&self, string: &mut String, char: char) -> Result<(), ParseError> {
+        if let Some(max) = self.options.max_string_len {
+            if string.len() + char.len_utf8() > max {
+                return Err(ParseError::StringTooLong);
+            }
+        }
+        string.push(char);
+        Ok(())
+    }
+
+    fn read_hex4(&mut self) -> Result<u32, ParseError> {
+        let mut code = 0;
+        for _ in 0..4 {
+            let char = self.input.next().ok_or(ParseError::UnexpectedEndOfInput)?;
+            let digit = char.to_digit(16).ok_or(ParseError::InvalidUnicodeEscape)?;
+            code = code * 16 + digit;
+        }
+        Ok(code)
+    }
+
+    fn read_number(&mut self, first: char) -> Result<Token, ParseError> {
+        if self.options.json5 {
+            match (first, self.input.peek()) {
+                ('+', _) => {
+                    return match self.input.next() {
+                        Some(char @ ('0'..='9' | '.')) => self.read_number(char),
+                        Some('I') => assert_next_chars(&mut self.input, "Infinity")
+                            .map(|_| Token::Number(f64::INFINITY)),
+                        Some(_) => Err(ParseError::InvalidToken),
+                        None => Err(ParseError::UnexpectedEndOfInput),
+                    };
+                }
+                ('-', Some('I')) => {
+                    self.input.next();
+                    return assert_next_chars(&mut self.input, "Infinity")
+                        .map(|_| Token::Number(f64::NEG_INFINITY));
+                }
+                ('0', Some('x' | 'X')) => {
+                    self.input.next();
+                    return self.read_hex_number();
+                }
+                _ => {}
+            }
+        }
+
+        let mut number = String::new();
+        number.push(first);
+
+        while let Some(&char) = self.input.peek() {
+            match char {
+                '0'..='9' | '.' | 'e' | 'E' => {
+                    number.push(char);
+                    self.input.next();
+                }
+                '+' | '-' if number.ends_with(['e', 'E']) => {
+                    number.push(char);
+                    self.input.next();
+                }
+                _ => break,
+            }
+        }
+
+        if !self.options.json5 && !is_json_number(&number) {
+            return Err(match number.parse::<f64>() {
+                Ok(_) => ParseError::MalformedNumber(number),
+                Err(error) => ParseError::InvalidNumber(error),
+            });
+        }
 
-                Token::Number(number.parse().map_err(ParseError::InvalidNumber)?)
+        if !number.contains(['.', 'e', 'E']) {
+            if let Ok(value) = number.parse::<i64>() {
+                return Ok(Token::Integer(value));
             }
-            't' => assert_next_chars(&mut input, "rue").map(|_| Token::Boolean(true))?,
-            'f' => assert_next_chars(&mut input, "alse").map(|_| Token::Boolean(false))?,
-            'n' => assert_next_chars(&mut input, "ull").map(|_| Token::Null)?,
-            ' ' | '\n' | '\t' => continue,
-            _ => return Err(ParseError::InvalidToken),
-        };
+            if self.options.wide_integers {
+                if let Ok(value) = number.parse::<i128>() {
+                    return Ok(Token::WideInteger(value));
+                }
+            }
+        }
+
+        let value = number.parse().map_err(ParseError::InvalidNumber)?;
+        if self.options.warn_on_precision_loss && loses_precision(&number, value) {
+            self.precision_loss.push(number);
+        }
+
+        Ok(Token::Number(value))
+    }
+
+    fn read_hex_number(&mut self) -> Result<Token, ParseError> {
+        let mut digits = String::new();
 
-        tokens.push(token);
+        while let Some(&char) = self.input.peek().filter(|char| char.is_ascii_hexdigit()) {
+            digits.push(char);
+            self.input.next();
+        }
+
+        if digits.is_empty() {
+            return Err(ParseError::InvalidToken);
+        }
+
+        Ok(match i64::from_str_radix(&digits, 16) {
+            Ok(value) => Token::Integer(value),
+            Err(_) => Token::Number(digits.chars().fold(0.0, |value, digit| {
+                value * 16.0 + f64::from(digit.to_digit(16).unwrap())
+            })),
+        })
     }
+}
 
-    Ok(tokens)
+/// A peekable source of characters that counts the bytes consumed so far and
+/// tracks the position of the last one.
+struct Source<I: Iterator<Item = char>> {
+    chars: Peekable<I>,
+    offset: usize,
+    position: Position,
+    after_newline: bool,
 }
 
-fn assert_next_chars(input: &mut Peekable<Chars>, expected: &str) -> Result<(), ParseError> {
-    let mut next_chars = vec![];
-    for _ in 0..expected.len() {
-        next_chars.push(input.next().ok_or(ParseError::UnexpectedEndOfInput)?);
+impl<I: Iterator<Item = char>> Source<I> {
+    fn peek(&mut self) -> Option<&char> {
+        self.chars.peek()
     }
+}
 
-    if next_chars.iter().collect::<String>().as_str() == expected {
-        Ok(())
-    } else {
-        Err(ParseError::UnexpectedEndOfInput)
+impl<I: Iterator<Item = char>> Iterator for Source<I> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let char = self.chars.next()?;
+        self.offset += char.len_utf8();
+
+        if self.after_newline {
+            self.position.line += 1;
+            self.position.column = 1;
+        } else {
+            self.position.column += 1;
+        }
+        self.after_newline = char == '\n';
+
+        Some(char)
+    }
+}
+
+impl<I: Iterator<Item = char>> Iterator for Tokenizer<I> {
+    type Item = Result<Token, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_token().transpose()
+    }
+}
+
+/// Checks `literal` against the RFC 8259 number grammar, which rules out
+/// leading zeros and empty fraction or exponent digits.
+fn is_json_number(literal: &str) -> bool {
+    fn skip_digits(input: &str) -> (usize, &str) {
+        let rest = input.trim_start_matches(|char: char| char.is_ascii_digit());
+        (input.len() - rest.len(), rest)
     }
+
+    let unsigned = literal.strip_prefix('-').unwrap_or(literal);
+    let (int_digits, mut rest) = skip_digits(unsigned);
+    if int_digits == 0 || (int_digits > 1 && unsigned.starts_with('0')) {
+        return false;
+    }
+
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let (digits, after) = skip_digits(fraction);
+        if digits == 0 {
+            return false;
+        }
+        rest = after;
+    }
+
+    if let Some(exponent) = rest.strip_prefix(['e', 'E']) {
+        let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        let (digits, after) = skip_digits(exponent);
+        if digits == 0 {
+            return false;
+        }
+        rest = after;
+    }
+
+    rest.is_empty()
+}
+
+/// Compares the significant digits of a literal with those of the shortest
+/// representation of the parsed value.
+fn loses_precision(literal: &str, value: f64) -> bool {
+    fn significant_digits(mantissa: &str) -> String {
+        let digits: String = mantissa.chars().filter(char::is_ascii_digit).collect();
+        digits.trim_matches('0').to_string()
+    }
+
+    if !value.is_finite() {
+        return true;
+    }
+
+    let literal_mantissa = literal.split(['e', 'E']).next().unwrap_or_default();
+    let formatted = format!("{:e}", value);
+    let value_mantissa = formatted.split('e').next().unwrap_or_default();
+
+    significant_digits(literal_mantissa) != significant_digits(value_mantissa)
+}
+
+/// Checks that the rest of `keyword` follows its already consumed first
+/// character.
+fn assert_next_chars(
+    input: &mut impl Iterator<Item = char>,
+    keyword: &'static str,
+) -> Result<(), ParseError> {
+    let mut found = keyword[..1].to_string();
+    for expected in keyword.chars().skip(1) {
+        let char = input.next().ok_or(ParseError::UnexpectedEndOfInput)?;
+        found.push(char);
+        if char != expected {
+            return Err(ParseError::InvalidKeyword {
+                expected: keyword,
+                found,
+            });
+        }
+    }
+
+    Ok(())
 }
 
 #[cfg(test)]
@@ -114,7 +701,388 @@ mod tests {
     fn test_tokenize_string() {
         let input = r#""hello world""#;
         let tokens = tokenize(input.to_string()).unwrap();
-        assert_eq!(tokens, vec![Token::String("hello world".to_string())]);
+        assert_eq!(tokens, vec![Token::String("hello world".into())]);
+    }
+
+    #[test]
+    fn test_tokenize_string_escapes() {
+        let cases = [
+            (r#""\"""#, "\""),
+            (r#""\\""#, "\\"),
+            (r#""\/""#, "/"),
+            (r#""\b""#, "\u{8}"),
+            (r#""\f""#, "\u{c}"),
+            (r#""line\nbreak""#, "line\nbreak"),
+            (r#""\r""#, "\r"),
+            (r#""\t""#, "\t"),
+            (r#""a\\""#, "a\\"),
+        ];
+
+        for (input, expected) in cases {
+            let tokens = tokenize(input.to_string()).unwrap();
+            assert_eq!(tokens, vec![Token::String(expected.into())], "{}", input);
+        }
+
+        assert_eq!(
+            tokenize(r#""\x""#.to_string()),
+            Err(ParseError::InvalidToken)
+        );
+        assert_eq!(
+            tokenize(r#""a\"#.to_string()),
+            Err(ParseError::UnexpectedEndOfInput)
+        );
+    }
+
+    #[test]
+    fn test_tokenize_unicode_escapes() {
+        let cases = [
+            (r#""\u0041""#, "A"),
+            (r#""caf\u00e9""#, "café"),
+            (r#""\u00E9\u4e2d""#, "é中"),
+            (r#""\uD83D\uDE00""#, "😀"),
+        ];
+
+        for (input, expected) in cases {
+            let tokens = tokenize(input.to_string()).unwrap();
+            assert_eq!(tokens, vec![Token::String(expected.into())], "{}", input);
+        }
+
+        for input in [
+            r#""\uD83D""#,
+            r#""\uD83Dx""#,
+            r#""\uD83D\u0041""#,
+            r#""\uDE00""#,
+            r#""\u00g1""#,
+        ] {
+            assert_eq!(
+                tokenize(input.to_string()),
+                Err(ParseError::InvalidUnicodeEscape),
+                "{}",
+                input
+            );
+        }
+        assert_eq!(
+            tokenize(r#""\u00"#.to_string()),
+            Err(ParseError::UnexpectedEndOfInput)
+        );
+    }
+
+    #[test]
+    fn test_tokenize_unterminated_string() {
+        assert_eq!(
+            tokenize(r#""hello"#.to_string()),
+            Err(ParseError::UnexpectedEndOfInput)
+        );
+        assert_eq!(
+            tokenize(r#"["a", "b]"#.to_string()),
+            Err(ParseError::UnexpectedEndOfInput)
+        );
+        assert_eq!(
+            tokenize("\"".to_string()),
+            Err(ParseError::UnexpectedEndOfInput)
+        );
+    }
+
+    #[test]
+    fn test_tokenize_lenient_surrogates() {
+        let options = ParseOptions {
+            lenient_surrogates: true,
+            ..Default::default()
+        };
+        let cases = [
+            (r#""\uD83D""#, "\u{fffd}"),
+            (r#""\uD83D\u0041""#, "\u{fffd}A"),
+            (r#""\uD83D\n""#, "\u{fffd}\n"),
+            (r#""\uDE00x""#, "\u{fffd}x"),
+            (r#""\uD83D\uD83D\uDE00""#, "\u{fffd}😀"),
+        ];
+
+        for (input, expected) in cases {
+            let tokens = tokenize_with_options(input.to_string(), &options).unwrap();
+            assert_eq!(tokens, vec![Token::String(expected.into())], "{}", input);
+        }
+
+        assert_eq!(
+            tokenize_with_options(r#""\uD83D\q""#.to_string(), &options),
+            Err(ParseError::InvalidToken)
+        );
+    }
+
+    #[test]
+    fn test_tokenize_max_string_len() {
+        let options = ParseOptions {
+            max_string_len: Some(5),
+            ..Default::default()
+        };
+
+        let tokens = tokenize_with_options(r#""hello""#.to_string(), &options).unwrap();
+        assert_eq!(tokens, vec![Token::String("hello".into())]);
+
+        let tokens = tokenize_with_options(r#""hello!""#.to_string(), &options);
+        assert_eq!(tokens, Err(ParseError::StringTooLong));
+
+        // The limit is hit long before the missing closing quote would be noticed.
+        let input = format!("\"{}", "a".repeat(10_000));
+        let tokens = tokenize_with_options(input, &options);
+        assert_eq!(tokens, Err(ParseError::StringTooLong));
+    }
+
+    #[test]
+    fn test_tokenize_interning_strings() {
+        let input = r#"{"a": "first value", "b": ["x", "", "caf\u00e9 \n", "x"]}"#;
+        let intern = ParseOptions {
+            intern_strings: true,
+            ..Default::default()
+        };
+
+        let tokens = tokenize_with_options(input.to_string(), &intern).unwrap();
+        assert_eq!(Ok(tokens.clone()), tokenize(input.to_string()));
+
+        let strings: Vec<_> = tokens
+            .iter()
+            .filter_map(|token| match token {
+                Token::String(value) => Some(value),
+                _ => None,
+            })
+            .collect();
+        assert!(Arc::ptr_eq(strings[3], strings[6]));
+
+        let long = "y".repeat(MAX_INTERNED_LEN + 1);
+        let tokens = tokenize_with_options(format!("[\"{0}\", \"{0}\"]", long), &intern).unwrap();
+        match (&tokens[1], &tokens[3]) {
+            (Token::String(a), Token::String(b)) => assert!(!Arc::ptr_eq(a, b)),
+            other => panic!("Expected strings, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_tokenizer_yields_tokens_lazily() {
+        let input = "[1, true".chars().chain("a".chars());
+        let mut tokenizer = Tokenizer::new(input, ParseOptions::default());
+
+        assert_eq!(tokenizer.next(), Some(Ok(Token::OpenBracket)));
+        assert_eq!(tokenizer.next(), Some(Ok(Token::Integer(1))));
+        assert_eq!(tokenizer.next(), Some(Ok(Token::Comma)));
+        assert_eq!(tokenizer.next(), Some(Ok(Token::Boolean(true))));
+        assert_eq!(tokenizer.next(), Some(Err(ParseError::InvalidToken)));
+    }
+
+    #[test]
+    fn test_tokenizer_spans() {
+        let mut tokenizer = Tokenizer::new(" [\"é\", -12]".chars(), ParseOptions::default());
+        let spans: Vec<_> = std::iter::from_fn(|| tokenizer.next_spanned())
+            .map(|token| token.unwrap().1)
+            .collect();
+
+        assert_eq!(spans, [1..2, 2..6, 6..7, 8..11, 11..12]);
+    }
+
+    #[test]
+    fn test_tokenize_reports_error_position() {
+        let input = "{\n  \"a\": 1,\n  \"b\": @\n}";
+        let error = tokenize_with_position(input.to_string()).unwrap_err();
+        assert_eq!(
+            error,
+            PositionedError {
+                error: ParseError::InvalidToken,
+                position: Position { line: 3, column: 8 },
+            }
+        );
+        assert_eq!(error.to_string(), "invalid token at line 3, column 8");
+
+        let error = tokenize_with_position("[\n\n\"abc".to_string()).unwrap_err();
+        assert_eq!(error.error, ParseError::UnexpectedEndOfInput);
+        assert_eq!(error.position, Position { line: 3, column: 4 });
+
+        let json5 = ParseOptions {
+            json5: true,
+            ..Default::default()
+        };
+        let error = tokenize_with_position("[\n1,\n+2]".to_string()).unwrap_err();
+        assert_eq!(error.position, Position { line: 3, column: 1 });
+        assert_eq!(
+            tokenize_with_position_and_options("[\n1,\n+2]".to_string(), &json5),
+            Ok(tokenize_with_options("[1, 2]".to_string(), &json5).unwrap())
+        );
+
+        assert_eq!(
+            tokenize_with_position("[1]".to_string()),
+            Ok(vec![
+                Token::OpenBracket,
+                Token::Integer(1),
+                Token::CloseBracket
+            ])
+        );
+    }
+
+    #[test]
+    fn test_tokenize_comments() {
+        let options = ParseOptions {
+            comments: true,
+            ..Default::default()
+        };
+        let input = "// leading\n[1, /* two\n lines */ 2 /**/]// trailing";
+
+        assert_eq!(
+            tokenize_with_options(input.to_string(), &options),
+            Ok(vec![
+                Token::OpenBracket,
+                Token::Integer(1),
+                Token::Comma,
+                Token::Integer(2),
+                Token::CloseBracket,
+            ])
+        );
+        assert_eq!(
+            tokenize_with_options("[1 /* open".to_string(), &options),
+            Err(ParseError::UnexpectedEndOfInput)
+        );
+        assert_eq!(
+            tokenize_with_options("[1 / 2]".to_string(), &options),
+            Err(ParseError::InvalidToken)
+        );
+
+        assert_eq!(tokenize(input.to_string()), Err(ParseError::InvalidToken));
+        assert_eq!(
+            tokenize("[1 /* c */]".to_string()),
+            Err(ParseError::InvalidToken)
+        );
+    }
+
+    #[test]
+    fn test_tokenize_precision_loss() {
+        let options = ParseOptions {
+            warn_on_precision_loss: true,
+            ..Default::default()
+        };
+        let input = "[0.1, 12345678901234567890, 100, 1.5e300, 1e400, 0.000]";
+        let mut tokenizer = Tokenizer::new(input.chars(), options);
+
+        assert!(tokenizer.by_ref().all(|token| token.is_ok()));
+        assert_eq!(
+            tokenizer.precision_loss(),
+            ["12345678901234567890".to_string(), "1e400".to_string()]
+        );
+
+        let mut tokenizer = Tokenizer::new(input.chars(), ParseOptions::default());
+        assert!(tokenizer.by_ref().all(|token| token.is_ok()));
+        assert!(tokenizer.precision_loss().is_empty());
+    }
+
+    #[test]
+    fn test_tokenize_exact_integers_without_precision_loss() {
+        let options = ParseOptions {
+            warn_on_precision_loss: true,
+            ..Default::default()
+        };
+        let mut tokenizer =
+            Tokenizer::new("[9007199254740993, -9223372036854775808]".chars(), options);
+
+        assert_eq!(
+            tokenizer.by_ref().collect::<Result<Vec<_>, _>>(),
+            Ok(vec![
+                Token::OpenBracket,
+                Token::Integer(9007199254740993),
+                Token::Comma,
+                Token::Integer(i64::MIN),
+                Token::CloseBracket,
+            ])
+        );
+        assert!(tokenizer.precision_loss().is_empty());
+    }
+
+    #[test]
+    fn test_tokenize_json5_numbers() {
+        let json5 = ParseOptions {
+            json5: true,
+            ..Default::default()
+        };
+        let cases = [
+            ("+1", Token::Integer(1)),
+            ("0xFF", Token::Integer(255)),
+            ("+0x10", Token::Integer(16)),
+            ("0x10000000000000000", Token::Number(2f64.powi(64))),
+            (".5", Token::Number(0.5)),
+            ("5.", Token::Number(5.0)),
+            ("Infinity", Token::Number(f64::INFINITY)),
+            ("+Infinity", Token::Number(f64::INFINITY)),
+        ];
+
+        for (input, expected) in cases {
+            let tokens = tokenize_with_options(input.to_string(), &json5).unwrap();
+            assert_eq!(tokens, vec![expected], "{}", input);
+        }
+
+        let tokens = tokenize_with_options("NaN".to_string(), &json5).unwrap();
+        assert!(matches!(tokens[..], [Token::Number(value)] if value.is_nan()));
+
+        let tokens = tokenize_with_options("0x".to_string(), &json5);
+        assert_eq!(tokens, Err(ParseError::InvalidToken));
+
+        for input in ["+1", "0xFF", ".5", "Infinity", "NaN"] {
+            assert!(tokenize(input.to_string()).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_to_source_round_trip() {
+        let tokens = vec![
+            Token::OpenBrace,
+            Token::String("key".into()),
+            Token::Colon,
+            Token::OpenBracket,
+            Token::Number(42.69),
+            Token::Comma,
+            Token::Number(1e21),
+            Token::Comma,
+            Token::Integer(7),
+            Token::Comma,
+            Token::Boolean(false),
+            Token::Comma,
+            Token::Null,
+            Token::CloseBracket,
+            Token::CloseBrace,
+        ];
+
+        let source: String = tokens.iter().map(Token::to_source).collect();
+        assert_eq!(source, r#"{"key":[42.69,1e21,7,false,null]}"#);
+        assert_eq!(tokenize(source).unwrap(), tokens);
+    }
+
+    #[test]
+    fn test_to_source_preserves_decimal_form() {
+        let tokens = tokenize("[1.0, 1, 2.50, 3e2]".to_string()).unwrap();
+        let source: String = tokens.iter().map(Token::to_source).collect();
+        assert_eq!(source, "[1.0,1,2.5,300.0]");
+
+        assert_eq!(
+            tokenize("9007199254740993".to_string()).unwrap(),
+            vec![Token::Integer(9007199254740993)]
+        );
+        assert_eq!(
+            tokenize("12345678901234567890".to_string()).unwrap(),
+            vec![Token::Number(12345678901234567890.0)]
+        );
+    }
+
+    #[test]
+    fn test_token_approx_eq() {
+        let computed = Token::Number(0.1 + 0.2);
+        assert_ne!(computed, Token::Number(0.3));
+        assert!(computed.approx_eq(&Token::Number(0.3), 1e-9));
+        assert!(!computed.approx_eq(&Token::Number(0.31), 1e-9));
+
+        assert!(Token::Integer(3).approx_eq(&Token::Number(3.0000001), 1e-6));
+        assert!(Token::Number(f64::INFINITY).approx_eq(&Token::Number(f64::INFINITY), 0.0));
+        assert!(Token::Comma.approx_eq(&Token::Comma, 1.0));
+        assert!(!Token::Number(0.0).approx_eq(&Token::Null, 1.0));
+    }
+
+    #[test]
+    fn test_to_source_escapes_strings() {
+        let token = Token::String("a \"quote\"\n".into());
+        assert_eq!(token.to_source(), r#""a \"quote\"\n""#);
+        assert_eq!(Token::Number(f64::INFINITY).to_source(), "Infinity");
     }
 
     #[test]
@@ -128,6 +1096,80 @@ mod tests {
         assert_eq!(tokens, vec![Token::Number(12345.6)]);
     }
 
+    #[test]
+    fn test_tokenize_signed_exponent() {
+        assert_eq!(
+            tokenize("1e+5".to_string()).unwrap(),
+            vec![Token::Number(1e5)]
+        );
+        assert_eq!(
+            tokenize("1E-5".to_string()).unwrap(),
+            vec![Token::Number(1e-5)]
+        );
+        assert_eq!(
+            tokenize("6.022e23".to_string()).unwrap(),
+            vec![Token::Number(6.022e23)]
+        );
+        assert_eq!(
+            tokenize("[-2.5e-3]".to_string()).unwrap(),
+            vec![
+                Token::OpenBracket,
+                Token::Number(-2.5e-3),
+                Token::CloseBracket
+            ]
+        );
+
+        for input in ["1e--5", "1e+-5", "1e"] {
+            assert!(
+                matches!(
+                    tokenize(input.to_string()),
+                    Err(ParseError::InvalidNumber(_))
+                ),
+                "{}",
+                input
+            );
+        }
+        assert!(tokenize("1-5".to_string()).is_ok_and(|tokens| tokens.len() == 2));
+    }
+
+    #[test]
+    fn test_tokenize_negative_number() {
+        assert_eq!(
+            tokenize("-42".to_string()).unwrap(),
+            vec![Token::Integer(-42)]
+        );
+        assert_eq!(
+            tokenize("-3.25".to_string()).unwrap(),
+            vec![Token::Number(-3.25)]
+        );
+        assert_eq!(
+            tokenize("-1e3".to_string()).unwrap(),
+            vec![Token::Number(-1000.0)]
+        );
+        assert_eq!(
+            tokenize("[-0]".to_string()).unwrap(),
+            vec![Token::OpenBracket, Token::Integer(0), Token::CloseBracket]
+        );
+        assert!(matches!(
+            tokenize("-".to_string()),
+            Err(ParseError::InvalidNumber(_))
+        ));
+        assert!(matches!(
+            tokenize("[-]".to_string()),
+            Err(ParseError::InvalidNumber(_))
+        ));
+        assert!(tokenize("-Infinity".to_string()).is_err());
+
+        let json5 = ParseOptions {
+            json5: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            tokenize_with_options("-Infinity".to_string(), &json5).unwrap(),
+            vec![Token::Number(f64::NEG_INFINITY)]
+        );
+    }
+
     #[test]
     fn test_tokenize_literals() {
         let input = " truefalsenull";
@@ -140,6 +1182,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tokenize_malformed_numbers() {
+        for input in ["012", "-01", "1.", "-2.e3", "1.5e"] {
+            assert!(
+                matches!(
+                    tokenize(input.to_string()),
+                    Err(ParseError::MalformedNumber(_) | ParseError::InvalidNumber(_))
+                ),
+                "{}",
+                input
+            );
+        }
+        let error = tokenize("[012]".to_string()).unwrap_err();
+        assert_eq!(error, ParseError::MalformedNumber("012".to_string()));
+        assert_eq!(error.to_string(), "malformed number `012`");
+
+        let json5 = ParseOptions {
+            json5: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            tokenize_with_options("1.".to_string(), &json5).unwrap(),
+            vec![Token::Number(1.0)]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_control_characters() {
+        let error = tokenize("\"a\nb\"".to_string()).unwrap_err();
+        assert_eq!(error, ParseError::UnescapedControlCharacter('\n'));
+        assert_eq!(
+            error.to_string(),
+            "unescaped control character U+000A in string"
+        );
+        assert_eq!(
+            tokenize("\r\n[\r\n]".to_string()).unwrap(),
+            vec![Token::OpenBracket, Token::CloseBracket]
+        );
+    }
+
     #[test]
     fn test_tokenize_errors() {
         let input = "123.456.789";
@@ -153,5 +1235,20 @@ mod tests {
         let input = "tru";
         let tokens = tokenize(input.to_string());
         assert!(matches!(tokens, Err(ParseError::UnexpectedEndOfInput)));
+
+        let input = "trXe";
+        let error = tokenize(input.to_string()).unwrap_err();
+        assert_eq!(
+            error,
+            ParseError::InvalidKeyword {
+                expected: "true",
+                found: "trX".to_string()
+            }
+        );
+        assert_eq!(error.to_string(), "expected `true`, found `trX`");
+
+        let input = "[nul]";
+        let error = tokenize(input.to_string()).unwrap_err();
+        assert_eq!(error.to_string(), "expected `null`, found `nul]`");
     }
 }
@@ -0,0 +1,76 @@
+//! Lives in its own test binary because it installs a counting global
+//! allocator, which would otherwise apply to every unit test in the crate.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use chidjimi::parser::{
+    options::ParseOptions,
+    token::{tokenize, tokenize_with_options},
+};
+
+/// Counts the allocations made by the current thread, so tests running in
+/// parallel do not disturb each other.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+fn count_interned_allocations(input: String) -> usize {
+    let intern = ParseOptions {
+        intern_strings: true,
+        ..Default::default()
+    };
+
+    let (tokens, count) =
+        count_allocations(|| tokenize_with_options(input.clone(), &intern).unwrap());
+    assert_eq!(Ok(tokens), tokenize(input));
+    count
+}
+
+#[test]
+fn interning_shares_repeated_values() {
+    let repeated = format!("[{}\"\"]", "\"a repeated field value\", ".repeat(100));
+    let distinct: Vec<_> = (0..100)
+        .map(|i| format!("\"a distinct value {:05}\"", i))
+        .collect();
+    let distinct = format!("[{}, \"\"]", distinct.join(", "));
+    assert_eq!(repeated.len(), distinct.len());
+
+    let (_, fresh) = count_allocations(|| tokenize(repeated.clone()).unwrap());
+    let repeated = count_interned_allocations(repeated);
+    let distinct = count_interned_allocations(distinct);
+
+    // Both documents hold 100 strings of 22 bytes, but only the distinct
+    // values each need an allocation of their own.
+    assert!(repeated * 4 < distinct, "{} vs {}", repeated, distinct);
+    assert!(repeated * 4 < fresh, "{} vs {}", repeated, fresh);
+}
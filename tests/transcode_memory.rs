@@ -0,0 +1,102 @@
+//! Lives in its own test binary because it installs a measuring global
+//! allocator, which would otherwise apply to every unit test in the crate.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    io::{self, Cursor, Seek, SeekFrom, Write},
+};
+
+use chidjimi::message_pack::transcode::transcode_reader_to_msgpack;
+
+/// Tracks the bytes live on the current thread and their peak, so tests
+/// running in parallel do not disturb each other.
+struct MeasuringAllocator;
+
+thread_local! {
+    static LIVE: Cell<usize> = const { Cell::new(0) };
+    static PEAK: Cell<usize> = const { Cell::new(0) };
+}
+
+fn record(grown: usize, shrunk: usize) {
+    let _ = LIVE.try_with(|live| {
+        live.set((live.get() + grown).saturating_sub(shrunk));
+        let _ = PEAK.try_with(|peak| peak.set(peak.get().max(live.get())));
+    });
+}
+
+unsafe impl GlobalAlloc for MeasuringAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record(layout.size(), 0);
+        System.alloc(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        record(new_size, layout.size());
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        record(0, layout.size());
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: MeasuringAllocator = MeasuringAllocator;
+
+/// Returns the most bytes allocated at once while `f` runs, beyond what was
+/// already live.
+fn peak_allocation<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = LIVE.with(Cell::get);
+    PEAK.with(|peak| peak.set(before));
+    let result = f();
+    (result, PEAK.with(Cell::get) - before)
+}
+
+/// Discards what is written but keeps track of its length, so the output
+/// itself does not count towards the measured memory.
+#[derive(Default)]
+struct Sink {
+    position: u64,
+    len: u64,
+}
+
+impl Write for Sink {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.position += bytes.len() as u64;
+        self.len = self.len.max(self.position);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for Sink {
+    fn seek(&mut self, position: SeekFrom) -> io::Result<u64> {
+        self.position = match position {
+            SeekFrom::Start(offset) => offset,
+            SeekFrom::End(offset) => self.len.saturating_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.saturating_add_signed(offset),
+        };
+        Ok(self.position)
+    }
+}
+
+#[test]
+fn transcoding_a_large_array_uses_bounded_memory() {
+    let elements: Vec<String> = (0..100_000)
+        .map(|i| format!(r#"{{"id": {}, "name": "element {}"}}"#, i, i))
+        .collect();
+    let input = format!("[{}]", elements.join(","));
+    let mut sink = Sink::default();
+
+    let (result, peak) =
+        peak_allocation(|| transcode_reader_to_msgpack(Cursor::new(input.as_bytes()), &mut sink));
+
+    result.unwrap();
+    assert!(sink.len > 2_000_000, "{}", sink.len);
+    assert!(peak < 64 * 1024, "{}", peak);
+}